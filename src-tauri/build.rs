@@ -1,3 +1,51 @@
 fn main() {
-    tauri_build::build()
+    // Declaring the app commands makes each one need an `allow-<command>`
+    // permission: the local UI gets them from capabilities/default.json,
+    // remote service pages only the few in capabilities/services.json.
+    // Keep in sync with `generate_handler!` in lib.rs (a lib.rs test checks).
+    tauri_build::try_build(tauri_build::Attributes::new().app_manifest(
+        tauri_build::AppManifest::new().commands(&[
+            "get_recipes",
+            "get_services",
+            "switch_service",
+            "get_last_active_service",
+            "save_services_cmd",
+            "generate_service_id",
+            "rename_service_id",
+            "open_settings",
+            "restart_app",
+            "reload_service",
+            "get_badge_counts",
+            "report_badge_count",
+            "report_scroll_position",
+            "forward_web_notification",
+            "snooze_notifications",
+            "unsnooze_notifications",
+            "get_snooze_until",
+            "get_notification_history",
+            "clear_notification_history",
+            "get_service_resource_usage",
+            "focus_service",
+            "get_service_url",
+            "show_service_context_menu",
+            "get_preferences",
+            "save_preferences_cmd",
+            "set_sidebar_expanded",
+            "set_sidebar_width",
+            "apply_services",
+            "get_services_load_info",
+            "list_profiles",
+            "create_profile",
+            "switch_profile",
+            "delete_profile",
+            "list_services_snapshots",
+            "diff_services_snapshot",
+            "restore_services_snapshot",
+            "export_workspace",
+            "import_workspace",
+            "fetch_service_certificate",
+            "trust_service_certificate",
+        ]),
+    ))
+    .expect("failed to run tauri-build");
 }
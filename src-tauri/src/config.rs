@@ -1,8 +1,13 @@
+use crate::notify_schedule::{self, ScheduleRule};
+use crate::quiet_hours::{self, QuietHours};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fmt;
 use std::fs;
+use std::io::{self, Write};
 use std::path::{Path, PathBuf};
-use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Service {
@@ -24,12 +29,68 @@ pub struct Service {
     /// badges and the taskbar count). Absent/unknown is treated as `"all"`.
     #[serde(default)]
     pub notify: Option<String>,
-    /// When `true`, this service is exempt from hibernation: its webview is
-    /// never unloaded to `about:blank` for inactivity, so it keeps receiving
-    /// real-time updates (and therefore badges/notifications) in the
-    /// background. Defaults to `false` (hibernates normally after 10 minutes).
+    /// Time windows overriding `notify` (e.g. `"all"` during office hours,
+    /// `"badge"` otherwise); the first window covering the current local
+    /// time wins. Empty means `notify` applies all the time.
     #[serde(default)]
-    pub keep_alive: bool,
+    pub notify_schedule: Vec<ScheduleRule>,
+    /// When the service's webview is hibernated (closed to free memory).
+    /// Defaults to the global `hibernation_minutes` preference.
+    #[serde(default)]
+    pub hibernation: HibernationPolicy,
+    /// When `true`, only a rise of the direct count (mentions, DMs) triggers
+    /// a desktop notification and taskbar flash; channel activity (indirect
+    /// count) just updates the sidebar badge and stays out of the taskbar
+    /// count. Defaults to `false`: any new unread notifies and counts.
+    #[serde(default)]
+    pub notify_direct_only: bool,
+    /// Load the service in the background shortly after launch rather than
+    /// on its first click, so its badges and notifications work right away
+    /// (see `webviews::preload_services`).
+    #[serde(default)]
+    pub load_at_startup: bool,
+    /// Stylesheet injected into every page of the service (banner hiding,
+    /// dark-mode fixes…). Not part of services.json: stored as `custom.css`
+    /// in the service's webview_data dir (see [`load_service_customizations`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_css: Option<String>,
+    /// Script run in every page of the service once the DOM is ready. Stored
+    /// as `custom.js` next to `custom.css`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_js: Option<String>,
+}
+
+/// Per-service hibernation policy (see [`Service::hibernation`]).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum HibernationPolicy {
+    /// After the global `hibernation_minutes` of inactivity.
+    #[default]
+    Default,
+    /// Never: the service keeps running in the background, so it keeps
+    /// receiving real-time updates (and therefore badges/notifications).
+    Never,
+    /// After this many idle minutes, whatever the global preference says.
+    After { minutes: u32 },
+    /// As soon as another service (or the settings) is shown; for heavy apps.
+    OnSwitchAway,
+}
+
+impl HibernationPolicy {
+    /// Idle seconds after which the webview is hibernated, given the global
+    /// `hibernation_minutes` (`0` disables it); `None` means never.
+    pub fn idle_threshold_secs(self, default_minutes: u32) -> Option<u64> {
+        match self {
+            HibernationPolicy::Default => {
+                (default_minutes > 0).then(|| u64::from(default_minutes) * 60)
+            }
+            HibernationPolicy::Never => None,
+            HibernationPolicy::After { minutes } => Some(u64::from(minutes) * 60),
+            // Normally hibernated by the switch itself; the idle check is a
+            // fallback (e.g. after a failed close).
+            HibernationPolicy::OnSwitchAway => Some(0),
+        }
+    }
 }
 
 /// Notification levels (see [`Service::notify`]).
@@ -37,13 +98,27 @@ pub const NOTIFY_ALL: &str = "all";
 pub const NOTIFY_BADGE: &str = "badge";
 pub const NOTIFY_OFF: &str = "off";
 
+fn notify_level_of(value: Option<&str>) -> &'static str {
+    match value {
+        Some(NOTIFY_BADGE) => NOTIFY_BADGE,
+        Some(NOTIFY_OFF) => NOTIFY_OFF,
+        _ => NOTIFY_ALL,
+    }
+}
+
 impl Service {
     /// Normalized notification level; absent/unknown values fall back to `"all"`.
     pub fn notify_level(&self) -> &'static str {
-        match self.notify.as_deref() {
-            Some(NOTIFY_BADGE) => NOTIFY_BADGE,
-            Some(NOTIFY_OFF) => NOTIFY_OFF,
-            _ => NOTIFY_ALL,
+        notify_level_of(self.notify.as_deref())
+    }
+
+    /// Level in effect at `minute_of_day` on `weekday` (0 = Monday), local
+    /// time: the first [`Service::notify_schedule`] window covering it, else
+    /// [`Service::notify_level`].
+    pub fn notify_level_at(&self, weekday: u32, minute_of_day: u32) -> &'static str {
+        match notify_schedule::scheduled_level(&self.notify_schedule, weekday, minute_of_day) {
+            Some(level) => notify_level_of(Some(level)),
+            None => self.notify_level(),
         }
     }
 }
@@ -72,6 +147,28 @@ pub struct Preferences {
     /// process tree is closed to free memory). `0` disables hibernation.
     #[serde(default = "default_hibernation_minutes")]
     pub hibernation_minutes: u32,
+    /// Do Not Disturb schedule; notifications are held back while it is
+    /// active, badges still update.
+    #[serde(default)]
+    pub quiet_hours: QuietHours,
+    /// Minimum seconds between two badge notifications of the same service;
+    /// increases in between are merged into one. `0` disables the limit.
+    #[serde(default = "default_notification_interval_seconds")]
+    pub notification_interval_seconds: u32,
+    /// Memory (MB) Taurium and its web processes may use before the least
+    /// recently used services are hibernated, whatever their idle time
+    /// (Linux only, see `memory_monitor.rs`). `0` disables the limit.
+    #[serde(default)]
+    pub memory_limit_mb: u32,
+    /// Whether a service woken up from hibernation reopens the page it was
+    /// on; otherwise it always restarts from its home URL.
+    #[serde(default = "default_restore_last_page")]
+    pub restore_last_page: bool,
+    /// Whether bringing the window forward shortly after a notification
+    /// switches to its service, where clicks aren't reported (Windows,
+    /// macOS). Off by default: any alt-tab would count as a click.
+    #[serde(default)]
+    pub notification_focus_switches: bool,
 }
 
 fn default_icon_size() -> u32 {
@@ -98,9 +195,19 @@ fn default_sidebar_expanded() -> bool {
 pub(crate) fn default_hibernation_minutes() -> u32 {
     10
 }
+fn default_notification_interval_seconds() -> u32 {
+    10
+}
+fn default_restore_last_page() -> bool {
+    true
+}
+/// Upper bound for the notification interval (1h), for hand-edited files.
+const MAX_NOTIFICATION_INTERVAL_SECONDS: u32 = 3600;
 /// Upper bound for the hibernation delay (24h) — mostly a sanity cap for
 /// hand-edited preference files.
 const MAX_HIBERNATION_MINUTES: u32 = 1440;
+/// Upper bound for the memory limit (64 GB), for hand-edited files.
+const MAX_MEMORY_LIMIT_MB: u32 = 65536;
 
 impl Default for Preferences {
     fn default() -> Self {
@@ -112,6 +219,11 @@ impl Default for Preferences {
             notifications_enabled: default_notifications_enabled(),
             sidebar_expanded: default_sidebar_expanded(),
             hibernation_minutes: default_hibernation_minutes(),
+            quiet_hours: QuietHours::default(),
+            notification_interval_seconds: default_notification_interval_seconds(),
+            memory_limit_mb: 0,
+            restore_last_page: default_restore_last_page(),
+            notification_focus_switches: false,
         }
     }
 }
@@ -120,6 +232,12 @@ impl Default for Preferences {
 pub enum ConfigError {
     Io(std::io::Error),
     Json(serde_json::Error),
+    /// services.json was written by a newer Taurium (see
+    /// [`LoadServicesError::UnsupportedSchemaVersion`]): it was never loaded,
+    /// so saving would replace the user's services with this version's list.
+    NewerServicesFile {
+        found: u64,
+    },
 }
 
 impl fmt::Display for ConfigError {
@@ -127,6 +245,10 @@ impl fmt::Display for ConfigError {
         match self {
             ConfigError::Io(e) => write!(f, "{e}"),
             ConfigError::Json(e) => write!(f, "{e}"),
+            ConfigError::NewerServicesFile { found } => write!(
+                f,
+                "services.json uses schema version {found}, but this version of Taurium only supports up to {SERVICES_SCHEMA_VERSION}; it is left untouched"
+            ),
         }
     }
 }
@@ -136,6 +258,7 @@ impl std::error::Error for ConfigError {
         match self {
             ConfigError::Io(e) => Some(e),
             ConfigError::Json(e) => Some(e),
+            ConfigError::NewerServicesFile { .. } => None,
         }
     }
 }
@@ -158,6 +281,11 @@ pub enum LoadServicesError {
         backup_path: PathBuf,
         parse_error: serde_json::Error,
     },
+    /// The file was written by a newer Taurium (its `schema_version` is above
+    /// [`SERVICES_SCHEMA_VERSION`]). It is left in place, untouched.
+    UnsupportedSchemaVersion {
+        found: u64,
+    },
     Io(std::io::Error),
 }
 
@@ -173,6 +301,10 @@ impl fmt::Display for LoadServicesError {
                 backup_path.display(),
                 parse_error
             ),
+            LoadServicesError::UnsupportedSchemaVersion { found } => write!(
+                f,
+                "services.json uses schema version {found}, but this version of Taurium only supports up to {SERVICES_SCHEMA_VERSION}"
+            ),
             LoadServicesError::Io(e) => write!(f, "{e}"),
         }
     }
@@ -182,15 +314,30 @@ impl std::error::Error for LoadServicesError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             LoadServicesError::CorruptedJson { parse_error, .. } => Some(parse_error),
+            LoadServicesError::UnsupportedSchemaVersion { .. } => None,
             LoadServicesError::Io(e) => Some(e),
         }
     }
 }
 
+/// A services.json entry that couldn't be read and was left out of the list.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SkippedServiceEntry {
+    /// Position in the `services` array.
+    pub index: usize,
+    /// The entry's `id`, when it has a string one.
+    pub id: Option<String>,
+    pub error: String,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
 pub struct ServicesLoadInfo {
     pub filtered_url_count: usize,
+    /// Malformed entries dropped while the rest of the file was kept.
+    pub skipped_entries: Vec<SkippedServiceEntry>,
     pub load_error: Option<String>,
+    /// Names of the services.json migrations that ran during this load.
+    pub applied_migrations: Vec<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -200,12 +347,27 @@ pub struct LoadServicesResult {
     pub created_defaults: bool,
     /// Number of entries removed because the URL was invalid or not http(s).
     pub filtered_url_count: usize,
+    /// Entries that failed to deserialize on their own (the original file is
+    /// copied to `services.json.partial.bak` when there are any).
+    pub skipped_entries: Vec<SkippedServiceEntry>,
+    /// Names of the schema migrations applied (in order) before loading.
+    pub applied_migrations: Vec<String>,
 }
 
 pub fn load_preferences(app_data_dir: &Path) -> Preferences {
-    let path = app_data_dir.join("preferences.json");
+    let path = get_preferences_path(app_data_dir);
     let content = fs::read_to_string(&path).unwrap_or_else(|_| "{}".to_string());
-    let mut prefs: Preferences = serde_json::from_str(&content).unwrap_or_default();
+    parse_preferences(&content).unwrap_or_else(|_| normalize_preferences(Preferences::default()))
+}
+
+/// Strict counterpart of [`load_preferences`]: reports a malformed file
+/// instead of falling back to defaults, so a live reload can leave the
+/// current preferences alone.
+pub(crate) fn parse_preferences(content: &str) -> serde_json::Result<Preferences> {
+    serde_json::from_str(content).map(normalize_preferences)
+}
+
+fn normalize_preferences(mut prefs: Preferences) -> Preferences {
     // Migration : les anciens accents étaient des couleurs hexadécimales
     // libres ; tout ce qui n'est pas un preset V3 Snow retombe sur "blue".
     if !ACCENT_PRESETS.contains(&prefs.accent_color.as_str()) {
@@ -217,21 +379,400 @@ pub fn load_preferences(app_data_dir: &Path) -> Preferences {
     if prefs.hibernation_minutes > MAX_HIBERNATION_MINUTES {
         prefs.hibernation_minutes = MAX_HIBERNATION_MINUTES;
     }
+    prefs.quiet_hours = quiet_hours::normalize(prefs.quiet_hours);
+    prefs.notification_interval_seconds = prefs
+        .notification_interval_seconds
+        .min(MAX_NOTIFICATION_INTERVAL_SECONDS);
+    prefs.memory_limit_mb = prefs.memory_limit_mb.min(MAX_MEMORY_LIMIT_MB);
     prefs
 }
 
 pub fn save_preferences(app_data_dir: &Path, prefs: &Preferences) -> Result<(), ConfigError> {
-    let path = app_data_dir.join("preferences.json");
+    let path = get_preferences_path(app_data_dir);
     fs::create_dir_all(app_data_dir)?;
     let json = serde_json::to_string_pretty(prefs)?;
-    fs::write(&path, json)?;
+    write_atomic(&path, json.as_bytes())?;
+    Ok(())
+}
+
+/// Numbers the scratch files of this process, so that concurrent writers of
+/// one file (watcher reload, settings save, history…) never share one.
+static TEMP_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Scratch file a config file is written to before being renamed over it:
+/// `<file>.<pid>.<n>.tmp`, unique per call (and per instance).
+fn temp_path_for(path: &Path) -> PathBuf {
+    let mut name = path
+        .file_name()
+        .map(|n| n.to_os_string())
+        .unwrap_or_default();
+    let n = TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed);
+    name.push(format!(".{}.{n}.tmp", std::process::id()));
+    path.with_file_name(name)
+}
+
+/// First half of [`write_atomic`]: write `contents` to the scratch file next
+/// to `path` and flush it to disk. `path` itself is left untouched.
+fn write_temp_file(path: &Path, contents: &[u8]) -> io::Result<PathBuf> {
+    let tmp = temp_path_for(path);
+    let result = fs::File::create(&tmp).and_then(|mut file| {
+        file.write_all(contents)?;
+        file.sync_all()
+    });
+    if let Err(e) = result {
+        let _ = fs::remove_file(&tmp);
+        return Err(e);
+    }
+    Ok(tmp)
+}
+
+/// Second half of [`write_atomic`]: swap the flushed scratch file in place of
+/// `path` in a single rename, then flush the directory entry so the rename
+/// itself survives a power loss.
+fn commit_temp_file(tmp: &Path, path: &Path) -> io::Result<()> {
+    if let Err(e) = fs::rename(tmp, path) {
+        let _ = fs::remove_file(tmp);
+        return Err(e);
+    }
+    // Directories can't be opened as files on Windows; there the rename
+    // (MoveFileEx) is the best durability we get.
+    #[cfg(unix)]
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        fs::File::open(dir)?.sync_all()?;
+    }
     Ok(())
 }
 
+/// Crash-safe replacement for `fs::write`: the data goes to a scratch file
+/// next to `path` (see [`temp_path_for`]), is fsynced, then renamed over it.
+/// A crash at any point leaves either the previous file or the new one on
+/// disk — never a truncated mix of both, which `load_services` would
+/// otherwise treat as corrupted.
+pub(crate) fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let tmp = write_temp_file(path, contents)?;
+    commit_temp_file(&tmp, path)
+}
+
+/// Whether `name` is a scratch file of [`temp_path_for`] written by another
+/// process than this one.
+fn is_foreign_temp_name(name: &str) -> bool {
+    let Some(rest) = name.strip_suffix(".tmp") else {
+        return false;
+    };
+    let mut parts = rest.rsplitn(3, '.');
+    let (Some(n), Some(pid), Some(file)) = (parts.next(), parts.next(), parts.next()) else {
+        return false;
+    };
+    !file.is_empty()
+        && n.parse::<u64>().is_ok()
+        && pid
+            .parse::<u32>()
+            .is_ok_and(|pid| pid != std::process::id())
+}
+
+/// Remove the scratch files a crash left between the two halves of
+/// [`write_atomic`] in `dir`. Meant for startup, before this process writes
+/// anything there.
+pub fn remove_stale_temp_files(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        if !name.to_str().is_some_and(is_foreign_temp_name) {
+            continue;
+        }
+        match fs::remove_file(entry.path()) {
+            Ok(()) => eprintln!(
+                "[Taurium] Removed stale scratch file {}",
+                entry.path().display()
+            ),
+            Err(e) => eprintln!(
+                "[Taurium] Could not remove stale scratch file {}: {e}",
+                entry.path().display()
+            ),
+        }
+    }
+}
+
 pub fn get_services_path(app_data_dir: &Path) -> PathBuf {
     app_data_dir.join("services.json")
 }
 
+pub fn get_preferences_path(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("preferences.json")
+}
+
+/// Per-service browser profile (cookies, storage), named after the id.
+pub fn get_webview_data_dir(app_data_dir: &Path, id: &str) -> PathBuf {
+    app_data_dir.join("webview_data").join(id)
+}
+
+/// Current layout of services.json: `{"schema_version": N, "services": [...]}`.
+/// Version 1 is the legacy bare array, which has no version field.
+pub const SERVICES_SCHEMA_VERSION: u64 = 3;
+
+#[derive(Serialize)]
+struct ServicesFileRef<'a> {
+    schema_version: u64,
+    services: &'a [Service],
+}
+
+/// Entries stay raw here so one malformed service can't fail the whole file
+/// (see [`deserialize_service_entries`]).
+#[derive(Deserialize)]
+struct ServicesFile {
+    services: Vec<serde_json::Value>,
+}
+
+/// Deserialize each entry on its own, keeping the valid ones.
+fn deserialize_service_entries(
+    entries: Vec<serde_json::Value>,
+) -> (Vec<Service>, Vec<SkippedServiceEntry>) {
+    let mut services = Vec::with_capacity(entries.len());
+    let mut skipped = Vec::new();
+    for (index, entry) in entries.into_iter().enumerate() {
+        match Service::deserialize(&entry) {
+            Ok(service) => services.push(service),
+            Err(e) => skipped.push(SkippedServiceEntry {
+                index,
+                id: entry
+                    .get("id")
+                    .and_then(|id| id.as_str())
+                    .map(str::to_string),
+                error: e.to_string(),
+            }),
+        }
+    }
+    (services, skipped)
+}
+
+fn services_file_json(services: &[Service]) -> serde_json::Result<String> {
+    // Customizations live in their own files (save_service_customizations).
+    let services: Vec<Service> = services
+        .iter()
+        .cloned()
+        .map(|s| Service {
+            custom_css: None,
+            custom_js: None,
+            ..s
+        })
+        .collect();
+    serde_json::to_string_pretty(&ServicesFileRef {
+        schema_version: SERVICES_SCHEMA_VERSION,
+        services: &services,
+    })
+}
+
+const CUSTOM_CSS_FILE: &str = "custom.css";
+const CUSTOM_JS_FILE: &str = "custom.js";
+
+fn read_customization(path: &Path) -> Option<String> {
+    fs::read_to_string(path)
+        .ok()
+        .filter(|content| !content.trim().is_empty())
+}
+
+/// Write (or remove, for `None`) one customization file, leaving it alone
+/// when it already holds `content`.
+fn write_customization(path: &Path, content: Option<&str>) -> io::Result<()> {
+    match content {
+        Some(content) => {
+            if fs::read_to_string(path).ok().as_deref() == Some(content) {
+                return Ok(());
+            }
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            write_atomic(path, content.as_bytes())
+        }
+        None => match fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        },
+    }
+}
+
+/// Fill `custom_css` / `custom_js` from each service's webview_data dir.
+pub(crate) fn load_service_customizations(app_data_dir: &Path, services: &mut [Service]) {
+    for service in services {
+        let dir = get_webview_data_dir(app_data_dir, &service.id);
+        service.custom_css = read_customization(&dir.join(CUSTOM_CSS_FILE));
+        service.custom_js = read_customization(&dir.join(CUSTOM_JS_FILE));
+    }
+}
+
+/// services.json `content` with each service's custom CSS / JS put back into
+/// its entry, for copies of the file (snapshots, workspace exports) that have
+/// to carry them. Returned as is when there is nothing to add or it doesn't
+/// parse.
+pub(crate) fn embed_service_customizations(app_data_dir: &Path, content: &[u8]) -> Vec<u8> {
+    let Ok(mut document) = serde_json::from_slice::<serde_json::Value>(content) else {
+        return content.to_vec();
+    };
+    let Some(entries) = document
+        .get_mut("services")
+        .and_then(|services| services.as_array_mut())
+    else {
+        return content.to_vec();
+    };
+    let mut embedded = false;
+    for entry in entries.iter_mut().filter_map(|e| e.as_object_mut()) {
+        let Some(id) = entry
+            .get("id")
+            .and_then(|id| id.as_str())
+            .filter(|id| is_safe_service_id(id))
+            .map(str::to_string)
+        else {
+            continue;
+        };
+        let dir = get_webview_data_dir(app_data_dir, &id);
+        for (field, file) in [
+            ("custom_css", CUSTOM_CSS_FILE),
+            ("custom_js", CUSTOM_JS_FILE),
+        ] {
+            if let Some(text) = read_customization(&dir.join(file)) {
+                entry.insert(field.to_string(), text.into());
+                embedded = true;
+            }
+        }
+    }
+    if !embedded {
+        return content.to_vec();
+    }
+    serde_json::to_vec_pretty(&document).unwrap_or_else(|_| content.to_vec())
+}
+
+fn save_service_customizations(app_data_dir: &Path, services: &[Service]) -> io::Result<()> {
+    for service in services {
+        // Never create a directory for an id that isn't a plain component.
+        if !is_safe_service_id(&service.id) {
+            continue;
+        }
+        let dir = get_webview_data_dir(app_data_dir, &service.id);
+        write_customization(&dir.join(CUSTOM_CSS_FILE), service.custom_css.as_deref())?;
+        write_customization(&dir.join(CUSTOM_JS_FILE), service.custom_js.as_deref())?;
+    }
+    Ok(())
+}
+
+/// One upgrade step of the services.json layout, from `from` to `from + 1`.
+/// Steps work on the raw JSON document so they never depend on the current
+/// shape of [`Service`].
+struct ServicesMigration {
+    from: u64,
+    name: &'static str,
+    migrate: fn(serde_json::Value) -> serde_json::Result<serde_json::Value>,
+}
+
+/// Ordered upgrade chain. To change the layout, bump
+/// [`SERVICES_SCHEMA_VERSION`] and append a step — never edit a released one.
+const SERVICES_MIGRATIONS: &[ServicesMigration] = &[
+    ServicesMigration {
+        from: 1,
+        name: "v1_to_v2_envelope",
+        migrate: migrate_v1_to_v2_envelope,
+    },
+    ServicesMigration {
+        from: 2,
+        name: "v2_to_v3_hibernation_policy",
+        migrate: migrate_v2_to_v3_hibernation_policy,
+    },
+];
+
+/// v1 → v2: wrap the bare service array in the versioned envelope.
+fn migrate_v1_to_v2_envelope(doc: serde_json::Value) -> serde_json::Result<serde_json::Value> {
+    Ok(serde_json::json!({ "schema_version": 2, "services": doc }))
+}
+
+/// v2 → v3: the `keep_alive` flag becomes a hibernation policy
+/// (`keep_alive: true` is the `never` policy, `false` the default one).
+fn migrate_v2_to_v3_hibernation_policy(
+    mut doc: serde_json::Value,
+) -> serde_json::Result<serde_json::Value> {
+    if let Some(services) = doc.get_mut("services").and_then(|s| s.as_array_mut()) {
+        // Malformed entries are left for deserialize_service_entries to report.
+        for service in services.iter_mut().filter_map(|s| s.as_object_mut()) {
+            if service.remove("keep_alive").and_then(|v| v.as_bool()) == Some(true) {
+                service.insert(
+                    "hibernation".to_string(),
+                    serde_json::json!({ "mode": "never" }),
+                );
+            }
+        }
+    }
+    doc["schema_version"] = serde_json::json!(3);
+    Ok(doc)
+}
+
+/// Schema version of a parsed services.json, or `None` for an unknown layout.
+fn services_schema_version(doc: &serde_json::Value) -> Option<u64> {
+    match doc {
+        serde_json::Value::Array(_) => Some(1),
+        serde_json::Value::Object(map) => map.get("schema_version")?.as_u64(),
+        _ => None,
+    }
+}
+
+/// A migration step that ran, with the document as it was before the step.
+pub(crate) struct AppliedMigration {
+    from: u64,
+    name: &'static str,
+    before: serde_json::Value,
+}
+
+/// Upgrade `doc` from `version` to [`SERVICES_SCHEMA_VERSION`] step by step.
+fn migrate_services_document(
+    mut doc: serde_json::Value,
+    mut version: u64,
+) -> serde_json::Result<(serde_json::Value, Vec<AppliedMigration>)> {
+    let mut applied = Vec::new();
+    while version < SERVICES_SCHEMA_VERSION {
+        let step = SERVICES_MIGRATIONS
+            .iter()
+            .find(|m| m.from == version)
+            .ok_or_else(|| {
+                <serde_json::Error as serde::de::Error>::custom(format!(
+                    "no migration registered from schema version {version}"
+                ))
+            })?;
+        let before = doc.clone();
+        doc = (step.migrate)(doc)?;
+        applied.push(AppliedMigration {
+            from: step.from,
+            name: step.name,
+            before,
+        });
+        version += 1;
+    }
+    Ok((doc, applied))
+}
+
+/// Copy of services.json as it was before upgrading from schema `version`.
+fn services_migration_backup_path(path: &Path, version: u64) -> PathBuf {
+    path.with_file_name(format!("services.json.v{version}.bak"))
+}
+
+/// Back up every pre-migration version, then persist the upgraded document
+/// so the migrations only ever run once. Best-effort: if a backup can't be
+/// written the original file is left alone (the upgrade is then redone in
+/// memory on the next load).
+fn persist_services_migration(
+    path: &Path,
+    doc: &serde_json::Value,
+    applied: &[AppliedMigration],
+) -> io::Result<()> {
+    for step in applied {
+        let before = serde_json::to_string_pretty(&step.before)?;
+        write_atomic(
+            &services_migration_backup_path(path, step.from),
+            before.as_bytes(),
+        )?;
+    }
+    let json = serde_json::to_string_pretty(doc)?;
+    write_atomic(path, json.as_bytes())
+}
+
 /// Only `http`/`https` service URLs are accepted; everything else
 /// (`javascript:`, `file:`, `ftp:`, garbage…) is rejected.
 fn is_valid_service_url(raw: &str) -> bool {
@@ -253,7 +794,12 @@ fn default_services() -> Vec<Service> {
             zoom: None,
             group: Some("Personnel".to_string()),
             notify: None,
-            keep_alive: false,
+            hibernation: HibernationPolicy::Default,
+            custom_css: None,
+            custom_js: None,
+            notify_direct_only: false,
+            load_at_startup: false,
+            notify_schedule: Vec::new(),
         },
         Service {
             id: "default-gmail".to_string(),
@@ -264,7 +810,12 @@ fn default_services() -> Vec<Service> {
             zoom: None,
             group: Some("Personnel".to_string()),
             notify: None,
-            keep_alive: false,
+            hibernation: HibernationPolicy::Default,
+            custom_css: None,
+            custom_js: None,
+            notify_direct_only: false,
+            load_at_startup: false,
+            notify_schedule: Vec::new(),
         },
         Service {
             id: "default-discord".to_string(),
@@ -275,7 +826,12 @@ fn default_services() -> Vec<Service> {
             zoom: None,
             group: Some("Personnel".to_string()),
             notify: None,
-            keep_alive: false,
+            hibernation: HibernationPolicy::Default,
+            custom_css: None,
+            custom_js: None,
+            notify_direct_only: false,
+            load_at_startup: false,
+            notify_schedule: Vec::new(),
         },
         Service {
             id: "default-slack".to_string(),
@@ -286,7 +842,12 @@ fn default_services() -> Vec<Service> {
             zoom: None,
             group: Some("Travail".to_string()),
             notify: None,
-            keep_alive: false,
+            hibernation: HibernationPolicy::Default,
+            custom_css: None,
+            custom_js: None,
+            notify_direct_only: false,
+            load_at_startup: false,
+            notify_schedule: Vec::new(),
         },
     ]
 }
@@ -295,6 +856,13 @@ fn services_backup_path(path: &Path) -> PathBuf {
     path.with_file_name("services.json.bak")
 }
 
+/// Copy of a services.json with unreadable entries. Distinct from
+/// [`services_backup_path`], so loading such a file never overwrites the
+/// backup of an earlier, fully corrupted one.
+fn services_partial_backup_path(path: &Path) -> PathBuf {
+    path.with_file_name("services.json.partial.bak")
+}
+
 fn backup_corrupted_services_file(path: &Path) -> Result<PathBuf, LoadServicesError> {
     let backup_path = services_backup_path(path);
     if backup_path.exists() {
@@ -324,10 +892,20 @@ fn normalize_service(mut service: Service) -> Service {
         Some(NOTIFY_BADGE) | Some(NOTIFY_OFF) => {}
         _ => service.notify = None,
     }
+    if let HibernationPolicy::After { minutes } = &mut service.hibernation {
+        *minutes = (*minutes).clamp(1, MAX_HIBERNATION_MINUTES);
+    }
+    service.notify_schedule =
+        notify_schedule::normalize(std::mem::take(&mut service.notify_schedule));
+    for custom in [&mut service.custom_css, &mut service.custom_js] {
+        if custom.as_deref().is_some_and(|c| c.trim().is_empty()) {
+            *custom = None;
+        }
+    }
     service
 }
 
-fn sanitize_services(raw_services: Vec<Service>) -> (Vec<Service>, usize) {
+pub(crate) fn sanitize_services(raw_services: Vec<Service>) -> (Vec<Service>, usize) {
     let total = raw_services.len();
     let services: Vec<Service> = raw_services
         .into_iter()
@@ -338,29 +916,322 @@ fn sanitize_services(raw_services: Vec<Service>) -> (Vec<Service>, usize) {
     (services, filtered_url_count)
 }
 
+/// One problem found by [`validate_services`], addressed by position and
+/// field so the settings page can highlight it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ServiceFieldError {
+    /// Position in the submitted list.
+    pub index: usize,
+    /// `"id"`, `"name"` or `"url"`.
+    pub field: &'static str,
+    /// Machine-readable reason: `"empty"`, `"duplicate"`, `"invalid_id"`,
+    /// `"reserved_id"` or `"invalid_url"`.
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl fmt::Display for ServiceFieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "services[{}].{}: {}",
+            self.index, self.field, self.message
+        )
+    }
+}
+
+/// Ids name the service's `webview_data/<id>` directory and its webview
+/// label, so they are limited to the characters Tauri accepts in a label
+/// (`[A-Za-z0-9_-]`), which also keeps them a single plain path component on
+/// every platform.
+fn is_safe_service_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Labels of the app's own window and webviews. A service's webview is
+/// labelled with its id, so these can't be service ids.
+pub(crate) const RESERVED_SERVICE_IDS: [&str; 3] = ["main", "sidebar", "settings"];
+
+pub(crate) fn is_reserved_service_id(id: &str) -> bool {
+    RESERVED_SERVICE_IDS.contains(&id)
+}
+
+/// Check a list before it is saved. Unlike [`sanitize_services`] (which
+/// quietly drops bad entries from a file on disk), this reports every problem
+/// so nothing is persisted until the whole list is fixed.
+pub fn validate_services(services: &[Service]) -> Vec<ServiceFieldError> {
+    let mut errors = Vec::new();
+    let mut seen_ids = std::collections::HashSet::new();
+    for (index, service) in services.iter().enumerate() {
+        let mut problem = |field, code, message: String| {
+            errors.push(ServiceFieldError {
+                index,
+                field,
+                code,
+                message,
+            })
+        };
+        if service.id.trim().is_empty() {
+            problem("id", "empty", "id is empty".to_string());
+        } else if !is_safe_service_id(&service.id) {
+            problem(
+                "id",
+                "invalid_id",
+                format!(
+                    "id {:?} may only contain letters, digits, '-' and '_'",
+                    service.id
+                ),
+            );
+        } else if is_reserved_service_id(&service.id) {
+            problem(
+                "id",
+                "reserved_id",
+                format!("id {:?} is used by Taurium itself", service.id),
+            );
+        } else if !seen_ids.insert(service.id.as_str()) {
+            problem(
+                "id",
+                "duplicate",
+                format!("id {:?} is used twice", service.id),
+            );
+        }
+        if service.name.trim().is_empty() {
+            problem("name", "empty", "name is empty".to_string());
+        }
+        if !is_valid_service_url(&service.url) {
+            problem(
+                "url",
+                "invalid_url",
+                format!("{:?} is not an http(s) URL", service.url),
+            );
+        }
+        if let Some(position) = service.notify_schedule.iter().position(|r| !r.is_valid()) {
+            problem(
+                "notify_schedule",
+                "invalid_schedule",
+                format!("notification schedule window {} is invalid", position + 1),
+            );
+        }
+    }
+    errors
+}
+
+/// Lowercase ASCII slug of a name (`fallback` when nothing is left); every
+/// other run of characters becomes a single `-`. Always a safe id (see
+/// [`is_safe_service_id`]).
+fn slugify(name: &str, fallback: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        fallback.to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+/// Slug of `name`, suffixed `-2`, `-3`… until `taken` rejects it no more
+/// (same scheme as workspace imports).
+fn unique_slug(name: &str, fallback: &str, taken: impl Fn(&str) -> bool) -> String {
+    let base = slugify(name, fallback);
+    if !taken(&base) {
+        return base;
+    }
+    (2..)
+        .map(|n| format!("{base}-{n}"))
+        .find(|id| !taken(id))
+        .expect("unbounded suffix range")
+}
+
+/// New id for a service called `name`, unique within `existing` and never
+/// one of the [`RESERVED_SERVICE_IDS`].
+pub fn generate_service_id(name: &str, existing: &[Service]) -> String {
+    unique_slug(name, "service", |id| {
+        is_reserved_service_id(id) || existing.iter().any(|s| s.id == id)
+    })
+}
+
+/// Pauses between attempts to move a service's webview_data directory: the
+/// browser process of the webview just closed can keep it locked for a
+/// moment (WebView2 on Windows).
+const RENAME_RETRY_DELAYS_MS: [u64; 5] = [50, 100, 200, 400, 800];
+
+/// Run `op` until it succeeds, sleeping `delays` between attempts. Errors that
+/// waiting cannot fix are returned at once.
+fn retry_with_backoff<T>(delays: &[u64], mut op: impl FnMut() -> io::Result<T>) -> io::Result<T> {
+    let mut delays = delays.iter();
+    loop {
+        match op() {
+            Err(e)
+                if !matches!(
+                    e.kind(),
+                    io::ErrorKind::NotFound | io::ErrorKind::AlreadyExists
+                ) =>
+            {
+                let Some(&delay) = delays.next() else {
+                    return Err(e);
+                };
+                std::thread::sleep(std::time::Duration::from_millis(delay));
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Persist a service id rename: move `webview_data/<old>` to `<new>` (keeping
+/// the session), save `services` (already renamed) and follow the rename in
+/// state.json. The webview using the old directory must be closed first.
+pub fn rename_service_id(
+    app_data_dir: &Path,
+    services: &[Service],
+    old_id: &str,
+    new_id: &str,
+) -> Result<(), ConfigError> {
+    let old_dir = get_webview_data_dir(app_data_dir, old_id);
+    let new_dir = get_webview_data_dir(app_data_dir, new_id);
+    let moved = old_dir.exists();
+    if moved {
+        if new_dir.exists() {
+            return Err(ConfigError::Io(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} already exists", new_dir.display()),
+            )));
+        }
+        retry_with_backoff(&RENAME_RETRY_DELAYS_MS, || fs::rename(&old_dir, &new_dir)).map_err(
+            |e| {
+                io::Error::new(
+                    e.kind(),
+                    format!(
+                        "could not move {} ({e}); the service's session may still be in use, try again in a moment",
+                        old_dir.display()
+                    ),
+                )
+            },
+        )?;
+    }
+
+    // Best-effort, like every save from the settings page.
+    let _ = snapshot_services(app_data_dir);
+    if let Err(err) = save_services(app_data_dir, services) {
+        if moved {
+            let _ = fs::rename(&new_dir, &old_dir);
+        }
+        return Err(err);
+    }
+
+    update_state(app_data_dir, |state| {
+        if state.last_active_service.as_deref() == Some(old_id) {
+            state.last_active_service = Some(new_id.to_string());
+        }
+        if let Some(page) = state.last_pages.remove(old_id) {
+            state.last_pages.insert(new_id.to_string(), page);
+        }
+    })
+}
+
+pub(crate) enum ParsedServices {
+    /// Entries, plus the upgraded document and the steps that produced it
+    /// (empty when the file was already current).
+    Current {
+        services: Vec<Service>,
+        skipped: Vec<SkippedServiceEntry>,
+        doc: serde_json::Value,
+        applied: Vec<AppliedMigration>,
+    },
+    /// Written by a newer Taurium; carries the unsupported version.
+    TooNew(u64),
+}
+
+/// Parse services.json, upgrading older layouts in memory on the way.
+pub(crate) fn parse_services_document(content: &str) -> serde_json::Result<ParsedServices> {
+    let doc: serde_json::Value = serde_json::from_str(content)?;
+    let version = services_schema_version(&doc).ok_or_else(|| {
+        <serde_json::Error as serde::de::Error>::custom("unrecognized services.json layout")
+    })?;
+    if version > SERVICES_SCHEMA_VERSION {
+        return Ok(ParsedServices::TooNew(version));
+    }
+    let (doc, applied) = migrate_services_document(doc, version)?;
+    let file = ServicesFile::deserialize(&doc)?;
+    let (services, skipped) = deserialize_service_entries(file.services);
+    Ok(ParsedServices::Current {
+        services,
+        skipped,
+        doc,
+        applied,
+    })
+}
+
 pub fn load_services(app_data_dir: &Path) -> Result<LoadServicesResult, LoadServicesError> {
     let path = get_services_path(app_data_dir);
 
     if !path.exists() {
         fs::create_dir_all(app_data_dir).map_err(LoadServicesError::Io)?;
         let defaults = default_services();
-        let json = serde_json::to_string_pretty(&defaults).map_err(|err| {
+        let json = services_file_json(&defaults).map_err(|err| {
             LoadServicesError::Io(std::io::Error::new(
                 std::io::ErrorKind::InvalidData,
                 err.to_string(),
             ))
         })?;
-        fs::write(&path, json).map_err(LoadServicesError::Io)?;
+        write_atomic(&path, json.as_bytes()).map_err(LoadServicesError::Io)?;
         return Ok(LoadServicesResult {
             services: defaults,
             created_defaults: true,
             filtered_url_count: 0,
+            skipped_entries: Vec::new(),
+            applied_migrations: Vec::new(),
         });
     }
 
     let content = fs::read_to_string(&path).map_err(LoadServicesError::Io)?;
-    let raw_services: Vec<Service> = match serde_json::from_str(&content) {
-        Ok(services) => services,
+    load_services_from_str(app_data_dir, &content)
+}
+
+/// [`load_services`] for a services.json the caller already read (the
+/// watcher checks it first): same backups, migration and sanitizing.
+pub fn load_services_from_str(
+    app_data_dir: &Path,
+    content: &str,
+) -> Result<LoadServicesResult, LoadServicesError> {
+    let path = get_services_path(app_data_dir);
+    let (raw_services, skipped, applied) = match parse_services_document(content) {
+        Ok(ParsedServices::Current {
+            services,
+            skipped,
+            doc,
+            applied,
+        }) => {
+            if !skipped.is_empty() {
+                // Keep the dropped entries recoverable: the next save rewrites
+                // services.json without them. Copy (not move) — the valid
+                // entries still load from the original. Every load (and
+                // watcher reload) of the same file lands here: write once.
+                let backup_path = services_partial_backup_path(&path);
+                if fs::read_to_string(&backup_path).ok().as_deref() != Some(content) {
+                    write_atomic(&backup_path, content.as_bytes())
+                        .map_err(LoadServicesError::Io)?;
+                }
+            }
+            if !applied.is_empty() {
+                // Best-effort (see persist_services_migration); the upgraded
+                // entries are used either way.
+                let _ = persist_services_migration(&path, &doc, &applied);
+            }
+            (services, skipped, applied)
+        }
+        Ok(ParsedServices::TooNew(found)) => {
+            return Err(LoadServicesError::UnsupportedSchemaVersion { found });
+        }
         Err(parse_error) => {
             let backup_path = backup_corrupted_services_file(&path)?;
             return Err(LoadServicesError::CorruptedJson {
@@ -370,85 +1241,546 @@ pub fn load_services(app_data_dir: &Path) -> Result<LoadServicesResult, LoadServ
         }
     };
 
-    let (services, filtered_url_count) = sanitize_services(raw_services);
+    let (mut services, filtered_url_count) = sanitize_services(raw_services);
+    load_service_customizations(app_data_dir, &mut services);
 
     Ok(LoadServicesResult {
         services,
         created_defaults: false,
         filtered_url_count,
+        skipped_entries: skipped,
+        applied_migrations: applied.iter().map(|m| m.name.to_string()).collect(),
     })
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct AppState {
     pub last_active_service: Option<String>,
+    /// Page each hibernated service was on, by service id, reopened when it
+    /// wakes up (see [`take_last_page`]).
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub last_pages: HashMap<String, LastPage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LastPage {
+    pub url: String,
+    /// `Service::url` when the page was recorded: if the service has been
+    /// pointed elsewhere since, the page is dropped.
+    pub home: String,
+    /// Vertical scroll position (`window.scrollY`) on `url`.
+    #[serde(default)]
+    pub scroll_y: u32,
 }
 
+/// Serializes the read-modify-write of state.json (switches, hibernation and
+/// wake-ups run on different threads).
+static STATE_LOCK: Mutex<()> = Mutex::new(());
+
 pub fn load_state(app_data_dir: &Path) -> AppState {
     let path = app_data_dir.join("state.json");
     let content = fs::read_to_string(&path).unwrap_or_else(|_| "{}".to_string());
     serde_json::from_str(&content).unwrap_or_default()
 }
 
+/// Schema version of the services.json at `path` when it comes from a newer
+/// Taurium.
+fn newer_services_file_version(path: &Path) -> Option<u64> {
+    let doc: serde_json::Value = serde_json::from_str(&fs::read_to_string(path).ok()?).ok()?;
+    services_schema_version(&doc).filter(|version| *version > SERVICES_SCHEMA_VERSION)
+}
+
+/// Refuses to overwrite a services.json from a newer Taurium
+/// ([`ConfigError::NewerServicesFile`]).
 pub fn save_services(app_data_dir: &Path, services: &[Service]) -> Result<(), ConfigError> {
     let path = get_services_path(app_data_dir);
+    if let Some(found) = newer_services_file_version(&path) {
+        return Err(ConfigError::NewerServicesFile { found });
+    }
     fs::create_dir_all(app_data_dir)?;
-    let json = serde_json::to_string_pretty(services)?;
-    fs::write(&path, json)?;
+    let json = services_file_json(services)?;
+    write_atomic(&path, json.as_bytes())?;
+    save_service_customizations(app_data_dir, services)?;
     Ok(())
 }
 
-pub fn save_state(app_data_dir: &Path, state: &AppState) -> Result<(), ConfigError> {
-    let path = app_data_dir.join("state.json");
-    fs::create_dir_all(app_data_dir)?;
-    let json = serde_json::to_string_pretty(state)?;
-    fs::write(&path, json)?;
-    Ok(())
+/// Number of services.json snapshots kept in `services_history/`.
+pub const SERVICES_HISTORY_LIMIT: usize = 20;
+
+fn services_history_dir(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("services_history")
 }
 
-/// Upper bound for badge counts extracted from page titles (years, IDs, etc. are ignored).
-const MAX_BADGE_COUNT: u32 = 999;
+/// Snapshot ids are generated here (`<unix millis>` or `<unix millis>-<n>`);
+/// anything else coming from the frontend is rejected so it can never
+/// escape `services_history/`.
+fn is_valid_snapshot_id(id: &str) -> bool {
+    !id.is_empty() && id.bytes().all(|b| b.is_ascii_digit() || b == b'-')
+}
 
-pub fn extract_badge_count(title: &str) -> u32 {
-    // Match patterns like "(3)", "(12)", "[5]" in page titles
-    static RE_PAREN: OnceLock<regex_lite::Regex> = OnceLock::new();
-    static RE_BRACKET: OnceLock<regex_lite::Regex> = OnceLock::new();
+fn snapshot_path(app_data_dir: &Path, id: &str) -> Result<PathBuf, ConfigError> {
+    if !is_valid_snapshot_id(id) {
+        return Err(ConfigError::Io(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("invalid snapshot id: {id}"),
+        )));
+    }
+    Ok(services_history_dir(app_data_dir).join(format!("{id}.json")))
+}
 
-    let re_paren = RE_PAREN.get_or_init(|| {
-        regex_lite::Regex::new(r"\((\d+)\)").expect("valid badge regex pattern for parentheses")
-    });
-    let re_bracket = RE_BRACKET.get_or_init(|| {
-        regex_lite::Regex::new(r"\[(\d+)\]").expect("valid badge regex pattern for brackets")
-    });
+/// A saved copy of services.json, as listed in the settings history.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ServicesSnapshot {
+    pub id: String,
+    /// Unix time in milliseconds at which the snapshot was taken.
+    pub timestamp_ms: u64,
+    /// `None` when the snapshot can't be parsed anymore.
+    pub service_count: Option<usize>,
+}
 
-    if let Some(caps) = re_paren.captures(title) {
-        if let Ok(n) = caps[1].parse::<u32>() {
-            if n > 0 && n <= MAX_BADGE_COUNT {
-                return n;
-            }
-        }
-    }
-    if let Some(caps) = re_bracket.captures(title) {
-        if let Ok(n) = caps[1].parse::<u32>() {
-            if n > 0 && n <= MAX_BADGE_COUNT {
-                return n;
-            }
+fn snapshot_timestamp(id: &str) -> Option<u64> {
+    id.split('-').next()?.parse().ok()
+}
+
+/// Snapshot ids sorted oldest first (timestamp, then collision suffix).
+fn snapshot_ids(app_data_dir: &Path) -> Result<Vec<String>, ConfigError> {
+    let dir = services_history_dir(app_data_dir);
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut ids: Vec<String> = Vec::new();
+    for entry in entries {
+        let name = entry?.file_name();
+        let Some(id) = name.to_str().and_then(|n| n.strip_suffix(".json")) else {
+            continue;
+        };
+        if is_valid_snapshot_id(id) && snapshot_timestamp(id).is_some() {
+            ids.push(id.to_string());
         }
     }
-    0
+    ids.sort_by_key(|id| {
+        let mut parts = id.splitn(2, '-');
+        let ts: u64 = parts.next().and_then(|p| p.parse().ok()).unwrap_or(0);
+        let seq: u64 = parts.next().and_then(|p| p.parse().ok()).unwrap_or(0);
+        (ts, seq)
+    });
+    Ok(ids)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::fs;
-    use tempfile::tempdir;
+pub(crate) fn now_unix_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
 
-    #[test]
-    fn hibernation_minutes_defaults_and_clamps() {
-        let dir = tempdir().unwrap();
+/// Copy the current services.json into the rolling history (before it gets
+/// overwritten) and prune the oldest snapshots beyond
+/// [`SERVICES_HISTORY_LIMIT`]. Returns the new snapshot id, or `None` when
+/// there is nothing to snapshot or the file is identical to the newest one.
+pub fn snapshot_services(app_data_dir: &Path) -> Result<Option<String>, ConfigError> {
+    snapshot_services_at(app_data_dir, now_unix_ms(), SERVICES_HISTORY_LIMIT)
+}
 
-        // Absent from the file → default of 10 minutes.
+fn snapshot_services_at(
+    app_data_dir: &Path,
+    now_ms: u64,
+    limit: usize,
+) -> Result<Option<String>, ConfigError> {
+    let current = match fs::read(get_services_path(app_data_dir)) {
+        Ok(content) => embed_service_customizations(app_data_dir, &content),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let mut ids = snapshot_ids(app_data_dir)?;
+    if let Some(newest) = ids.last() {
+        if fs::read(snapshot_path(app_data_dir, newest)?).ok().as_ref() == Some(&current) {
+            return Ok(None);
+        }
+    }
+
+    fs::create_dir_all(services_history_dir(app_data_dir))?;
+    let mut id = now_ms.to_string();
+    let mut seq = 0;
+    while ids.contains(&id) {
+        seq += 1;
+        id = format!("{now_ms}-{seq}");
+    }
+    write_atomic(&snapshot_path(app_data_dir, &id)?, &current)?;
+    ids.push(id.clone());
+
+    let excess = ids.len().saturating_sub(limit);
+    for old in &ids[..excess] {
+        fs::remove_file(snapshot_path(app_data_dir, old)?)?;
+    }
+    Ok(Some(id))
+}
+
+/// Snapshots available for restore, newest first.
+pub fn list_services_snapshots(app_data_dir: &Path) -> Result<Vec<ServicesSnapshot>, ConfigError> {
+    let mut snapshots = Vec::new();
+    for id in snapshot_ids(app_data_dir)?.into_iter().rev() {
+        let service_count = fs::read_to_string(snapshot_path(app_data_dir, &id)?)
+            .ok()
+            .and_then(|content| match parse_services_document(&content) {
+                Ok(ParsedServices::Current { services, .. }) => Some(services.len()),
+                _ => None,
+            });
+        snapshots.push(ServicesSnapshot {
+            timestamp_ms: snapshot_timestamp(&id).unwrap_or(0),
+            id,
+            service_count,
+        });
+    }
+    Ok(snapshots)
+}
+
+fn read_snapshot_services(app_data_dir: &Path, id: &str) -> Result<Vec<Service>, ConfigError> {
+    let content = fs::read_to_string(snapshot_path(app_data_dir, id)?)?;
+    match parse_services_document(&content)? {
+        ParsedServices::Current { services, .. } => Ok(services),
+        ParsedServices::TooNew(found) => Err(ConfigError::Io(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("snapshot uses unsupported schema version {found}"),
+        ))),
+    }
+}
+
+/// Service identity shown in a snapshot diff.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ServiceRef {
+    pub id: String,
+    pub name: String,
+}
+
+/// A service present on both sides whose settings differ.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChangedService {
+    pub id: String,
+    pub name: String,
+    /// JSON field names that differ (`url`, `zoom`, `notify`…).
+    pub fields: Vec<String>,
+}
+
+/// What restoring a snapshot would change, relative to the current services.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ServicesDiff {
+    /// In the snapshot but not in the current list (restoring brings them back).
+    pub added: Vec<ServiceRef>,
+    /// In the current list but not in the snapshot (restoring drops them).
+    pub removed: Vec<ServiceRef>,
+    pub changed: Vec<ChangedService>,
+    /// Same set of services, different sidebar order.
+    pub reordered: bool,
+}
+
+pub(crate) fn diff_services(current: &[Service], target: &[Service]) -> ServicesDiff {
+    let service_ref = |s: &Service| ServiceRef {
+        id: s.id.clone(),
+        name: s.name.clone(),
+    };
+    let mut diff = ServicesDiff::default();
+    for t in target {
+        match current.iter().find(|c| c.id == t.id) {
+            None => diff.added.push(service_ref(t)),
+            Some(c) => {
+                let (cv, tv) = (serde_json::to_value(c), serde_json::to_value(t));
+                let (Ok(serde_json::Value::Object(cv)), Ok(serde_json::Value::Object(tv))) =
+                    (cv, tv)
+                else {
+                    continue;
+                };
+                // Unset options are left out of the JSON: a field only one
+                // side has changed too.
+                let fields: Vec<String> = tv
+                    .keys()
+                    .chain(cv.keys().filter(|k| !tv.contains_key(*k)))
+                    .filter(|k| cv.get(*k) != tv.get(*k))
+                    .cloned()
+                    .collect();
+                if !fields.is_empty() {
+                    diff.changed.push(ChangedService {
+                        id: t.id.clone(),
+                        name: t.name.clone(),
+                        fields,
+                    });
+                }
+            }
+        }
+    }
+    diff.removed = current
+        .iter()
+        .filter(|c| !target.iter().any(|t| t.id == c.id))
+        .map(service_ref)
+        .collect();
+    let common = |list: &[Service], other: &[Service]| -> Vec<String> {
+        list.iter()
+            .filter(|s| other.iter().any(|o| o.id == s.id))
+            .map(|s| s.id.clone())
+            .collect()
+    };
+    diff.reordered = common(current, target) != common(target, current);
+    diff
+}
+
+/// Diff the current services.json against snapshot `id`.
+pub fn diff_services_snapshot(app_data_dir: &Path, id: &str) -> Result<ServicesDiff, ConfigError> {
+    let target = read_snapshot_services(app_data_dir, id)?;
+    let mut current = match fs::read_to_string(get_services_path(app_data_dir)) {
+        Ok(content) => match parse_services_document(&content) {
+            Ok(ParsedServices::Current { services, .. }) => services,
+            _ => Vec::new(),
+        },
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(e.into()),
+    };
+    load_service_customizations(app_data_dir, &mut current);
+    Ok(diff_services(&current, &target))
+}
+
+/// Replace services.json, and the customizations it carries, with snapshot
+/// `id`. The current file is snapshotted first, so a restore can itself be
+/// undone. The caller reloads the services (`load_services`) to apply them.
+pub fn restore_services_snapshot(app_data_dir: &Path, id: &str) -> Result<(), ConfigError> {
+    // Refuse snapshots that wouldn't load rather than swapping in garbage.
+    let services = read_snapshot_services(app_data_dir, id)?;
+    snapshot_services(app_data_dir)?;
+    let json = services_file_json(&services)?;
+    write_atomic(&get_services_path(app_data_dir), json.as_bytes())?;
+    save_service_customizations(app_data_dir, &services)?;
+    Ok(())
+}
+
+pub fn save_state(app_data_dir: &Path, state: &AppState) -> Result<(), ConfigError> {
+    let path = app_data_dir.join("state.json");
+    fs::create_dir_all(app_data_dir)?;
+    let json = serde_json::to_string_pretty(state)?;
+    write_atomic(&path, json.as_bytes())?;
+    Ok(())
+}
+
+/// Load state.json, let `change` edit it and save it back, without racing
+/// another update.
+pub fn update_state(
+    app_data_dir: &Path,
+    change: impl FnOnce(&mut AppState),
+) -> Result<(), ConfigError> {
+    let _guard = STATE_LOCK.lock().unwrap_or_else(|p| p.into_inner());
+    let mut state = load_state(app_data_dir);
+    change(&mut state);
+    save_state(app_data_dir, &state)
+}
+
+/// Remove and return the page recorded for service `id` at hibernation, if
+/// it still belongs to `home` (the service's current URL).
+pub fn take_last_page(app_data_dir: &Path, id: &str, home: &str) -> Option<LastPage> {
+    let _guard = STATE_LOCK.lock().unwrap_or_else(|p| p.into_inner());
+    let mut state = load_state(app_data_dir);
+    let page = state.last_pages.remove(id)?;
+    if let Err(e) = save_state(app_data_dir, &state) {
+        eprintln!("[Taurium] Failed to save state: {e}");
+    }
+    (page.home == home).then_some(page)
+}
+
+/// The profile that predates profiles: its files live directly in the data
+/// root, so existing installs keep working unchanged.
+pub const DEFAULT_PROFILE_ID: &str = "default";
+const DEFAULT_PROFILE_NAME: &str = "Default";
+
+/// A named set of services, preferences, state and `webview_data`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profile {
+    pub id: String,
+    pub name: String,
+}
+
+/// `profiles.json` in the data root. The default profile is implicit.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ProfilesFile {
+    #[serde(default)]
+    active: Option<String>,
+    #[serde(default)]
+    profiles: Vec<Profile>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProfilesInfo {
+    pub active: String,
+    /// Default profile first, then the others in creation order.
+    pub profiles: Vec<Profile>,
+}
+
+fn get_profiles_path(data_root: &Path) -> PathBuf {
+    data_root.join("profiles.json")
+}
+
+/// Directory holding a profile's files (what every other function here calls
+/// `app_data_dir`).
+pub fn get_profile_dir(data_root: &Path, id: &str) -> PathBuf {
+    if id == DEFAULT_PROFILE_ID {
+        data_root.to_path_buf()
+    } else {
+        data_root.join("profiles").join(id)
+    }
+}
+
+fn load_profiles_file(data_root: &Path) -> ProfilesFile {
+    fs::read_to_string(get_profiles_path(data_root))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_profiles_file(data_root: &Path, file: &ProfilesFile) -> Result<(), ConfigError> {
+    fs::create_dir_all(data_root)?;
+    let json = serde_json::to_string_pretty(file)?;
+    write_atomic(&get_profiles_path(data_root), json.as_bytes())?;
+    Ok(())
+}
+
+fn profile_not_found(id: &str) -> ConfigError {
+    ConfigError::Io(io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("unknown profile {id:?}"),
+    ))
+}
+
+pub fn list_profiles(data_root: &Path) -> ProfilesInfo {
+    let file = load_profiles_file(data_root);
+    let mut profiles = vec![Profile {
+        id: DEFAULT_PROFILE_ID.to_string(),
+        name: DEFAULT_PROFILE_NAME.to_string(),
+    }];
+    profiles.extend(
+        file.profiles
+            .into_iter()
+            .filter(|p| p.id != DEFAULT_PROFILE_ID && is_safe_service_id(&p.id)),
+    );
+    // A stale `active` (profile deleted by hand) falls back to the default.
+    let active = file
+        .active
+        .filter(|id| profiles.iter().any(|p| &p.id == id))
+        .unwrap_or_else(|| DEFAULT_PROFILE_ID.to_string());
+    ProfilesInfo { active, profiles }
+}
+
+/// Id and directory of the profile to open at startup.
+pub fn active_profile_dir(data_root: &Path) -> (String, PathBuf) {
+    let active = list_profiles(data_root).active;
+    let dir = get_profile_dir(data_root, &active);
+    (active, dir)
+}
+
+/// Create an empty profile (its services.json gets the defaults on first
+/// load). The id is a slug of the name, unique among existing profiles.
+pub fn create_profile(data_root: &Path, name: &str) -> Result<Profile, ConfigError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(ConfigError::Io(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "profile name is empty",
+        )));
+    }
+    let existing = list_profiles(data_root).profiles;
+    let profile = Profile {
+        id: unique_slug(name, "profile", |id| existing.iter().any(|p| p.id == id)),
+        name: name.to_string(),
+    };
+    fs::create_dir_all(get_profile_dir(data_root, &profile.id))?;
+    let mut file = load_profiles_file(data_root);
+    file.profiles.push(profile.clone());
+    save_profiles_file(data_root, &file)?;
+    Ok(profile)
+}
+
+/// Directory of an existing profile (created if missing on disk).
+pub fn find_profile_dir(data_root: &Path, id: &str) -> Result<PathBuf, ConfigError> {
+    if !list_profiles(data_root).profiles.iter().any(|p| p.id == id) {
+        return Err(profile_not_found(id));
+    }
+    let dir = get_profile_dir(data_root, id);
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Make `id` the active profile and return its directory.
+pub fn set_active_profile(data_root: &Path, id: &str) -> Result<PathBuf, ConfigError> {
+    let dir = find_profile_dir(data_root, id)?;
+    let mut file = load_profiles_file(data_root);
+    file.active = Some(id.to_string());
+    save_profiles_file(data_root, &file)?;
+    Ok(dir)
+}
+
+/// Delete a profile and all its data, sessions included. The default and
+/// the active profile can't be deleted.
+pub fn delete_profile(data_root: &Path, id: &str) -> Result<(), ConfigError> {
+    let info = list_profiles(data_root);
+    if id == DEFAULT_PROFILE_ID || id == info.active {
+        return Err(ConfigError::Io(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("profile {id:?} is in use and can't be deleted"),
+        )));
+    }
+    if !info.profiles.iter().any(|p| p.id == id) {
+        return Err(profile_not_found(id));
+    }
+    let mut file = load_profiles_file(data_root);
+    file.profiles.retain(|p| p.id != id);
+    save_profiles_file(data_root, &file)?;
+    let dir = get_profile_dir(data_root, id);
+    if dir.exists() {
+        fs::remove_dir_all(dir)?;
+    }
+    Ok(())
+}
+
+/// Upper bound for badge counts extracted from page titles (years, IDs, etc. are ignored).
+pub const MAX_BADGE_COUNT: u32 = 999;
+
+pub fn extract_badge_count(title: &str) -> u32 {
+    // Match patterns like "(3)", "(12)", "[5]" in page titles
+    static RE_PAREN: OnceLock<regex_lite::Regex> = OnceLock::new();
+    static RE_BRACKET: OnceLock<regex_lite::Regex> = OnceLock::new();
+
+    let re_paren = RE_PAREN.get_or_init(|| {
+        regex_lite::Regex::new(r"\((\d+)\)").expect("valid badge regex pattern for parentheses")
+    });
+    let re_bracket = RE_BRACKET.get_or_init(|| {
+        regex_lite::Regex::new(r"\[(\d+)\]").expect("valid badge regex pattern for brackets")
+    });
+
+    if let Some(caps) = re_paren.captures(title) {
+        if let Ok(n) = caps[1].parse::<u32>() {
+            if n > 0 && n <= MAX_BADGE_COUNT {
+                return n;
+            }
+        }
+    }
+    if let Some(caps) = re_bracket.captures(title) {
+        if let Ok(n) = caps[1].parse::<u32>() {
+            if n > 0 && n <= MAX_BADGE_COUNT {
+                return n;
+            }
+        }
+    }
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn hibernation_minutes_defaults_and_clamps() {
+        let dir = tempdir().unwrap();
+
+        // Absent from the file → default of 10 minutes.
         fs::write(dir.path().join("preferences.json"), "{}").unwrap();
         assert_eq!(load_preferences(dir.path()).hibernation_minutes, 10);
 
@@ -469,6 +1801,20 @@ mod tests {
         assert_eq!(load_preferences(dir.path()).hibernation_minutes, 1440);
     }
 
+    #[test]
+    fn memory_limit_is_off_by_default_and_capped() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("preferences.json"), "{}").unwrap();
+        assert_eq!(load_preferences(dir.path()).memory_limit_mb, 0);
+
+        fs::write(
+            dir.path().join("preferences.json"),
+            r#"{"memory_limit_mb": 4000000}"#,
+        )
+        .unwrap();
+        assert_eq!(load_preferences(dir.path()).memory_limit_mb, 65536);
+    }
+
     #[test]
     fn test_extract_badge_count() {
         assert_eq!(extract_badge_count("(3) Slack"), 3);
@@ -494,7 +1840,12 @@ mod tests {
             zoom: None,
             group: None,
             notify: notify.map(str::to_string),
-            keep_alive: false,
+            hibernation: HibernationPolicy::Default,
+            custom_css: None,
+            custom_js: None,
+            notify_direct_only: false,
+            load_at_startup: false,
+            notify_schedule: Vec::new(),
         }
     }
 
@@ -515,6 +1866,39 @@ mod tests {
         assert_eq!(service_with_notify(Some("")).notify_level(), NOTIFY_ALL);
     }
 
+    #[test]
+    fn notify_schedule_overrides_the_level_inside_its_windows() {
+        let mut slack = service_with_notify(Some("badge"));
+        slack.notify_schedule = vec![ScheduleRule {
+            days: vec![0, 1, 2, 3, 4],
+            start: "09:00".to_string(),
+            end: "18:00".to_string(),
+            level: NOTIFY_ALL.to_string(),
+        }];
+        assert_eq!(slack.notify_level_at(0, 10 * 60), NOTIFY_ALL);
+        assert_eq!(slack.notify_level_at(0, 20 * 60), NOTIFY_BADGE);
+        assert_eq!(slack.notify_level_at(5, 10 * 60), NOTIFY_BADGE);
+        assert_eq!(
+            service_with_notify(Some("off")).notify_level_at(0, 600),
+            NOTIFY_OFF
+        );
+
+        // Round trip through services.json; older files have no schedule.
+        let json = serde_json::to_value(&slack).unwrap();
+        assert_eq!(json["notify_schedule"][0]["level"], "all");
+        let back: Service = serde_json::from_value(json).unwrap();
+        assert_eq!(back.notify_schedule, slack.notify_schedule);
+        let mut old = serde_json::to_value(service_with_notify(None)).unwrap();
+        old.as_object_mut().unwrap().remove("notify_schedule");
+        let old: Service = serde_json::from_value(old).unwrap();
+        assert!(old.notify_schedule.is_empty());
+
+        slack.notify_schedule[0].start = "9h".to_string();
+        let problems = validate_services(std::slice::from_ref(&slack));
+        assert_eq!(problems[0].field, "notify_schedule");
+        assert!(normalize_service(slack).notify_schedule.is_empty());
+    }
+
     #[test]
     fn test_normalize_service_notify() {
         // "all"/unknown/empty are dropped to None; "badge"/"off" are kept.
@@ -605,6 +1989,580 @@ mod tests {
         }
     }
 
+    #[test]
+    fn legacy_services_file_is_migrated_once_with_backup() {
+        let dir = tempdir().expect("tempdir should be created");
+        let services_path = get_services_path(dir.path());
+        let legacy = r#"[{"id":"ok","name":"Ok","url":"https://example.com","icon":"x"}]"#;
+        fs::write(&services_path, legacy).unwrap();
+
+        let loaded = load_services(dir.path()).expect("legacy file should load");
+        assert_eq!(loaded.services.len(), 1);
+        assert_eq!(
+            loaded.applied_migrations,
+            vec!["v1_to_v2_envelope", "v2_to_v3_hibernation_policy"]
+        );
+
+        // The pre-migration file is kept verbatim (modulo formatting) and the
+        // upgraded envelope is written back.
+        let backup = services_migration_backup_path(&services_path, 1);
+        let backup_doc: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&backup).unwrap()).unwrap();
+        assert_eq!(
+            backup_doc,
+            serde_json::from_str::<serde_json::Value>(legacy).unwrap()
+        );
+        let upgraded: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&services_path).unwrap()).unwrap();
+        assert_eq!(upgraded["schema_version"], SERVICES_SCHEMA_VERSION);
+        assert_eq!(upgraded["services"][0]["id"], "ok");
+
+        // Second load: already current, nothing runs.
+        let again = load_services(dir.path()).unwrap();
+        assert!(again.applied_migrations.is_empty());
+        assert_eq!(again.services[0].id, "ok");
+    }
+
+    #[test]
+    fn keep_alive_becomes_the_never_hibernation_policy() {
+        let dir = tempdir().expect("tempdir should be created");
+        let services_path = get_services_path(dir.path());
+        let v2 = r#"{"schema_version": 2, "services": [
+            {"id":"chat","name":"Chat","url":"https://chat.example.com","icon":"x","keep_alive":true},
+            {"id":"mail","name":"Mail","url":"https://mail.example.com","icon":"x","keep_alive":false},
+            {"id":"news","name":"News","url":"https://news.example.com","icon":"x"}
+        ]}"#;
+        fs::write(&services_path, v2).unwrap();
+
+        let loaded = load_services(dir.path()).unwrap();
+        assert_eq!(
+            loaded.applied_migrations,
+            vec!["v2_to_v3_hibernation_policy"]
+        );
+        let policies: Vec<HibernationPolicy> =
+            loaded.services.iter().map(|s| s.hibernation).collect();
+        assert_eq!(
+            policies,
+            vec![
+                HibernationPolicy::Never,
+                HibernationPolicy::Default,
+                HibernationPolicy::Default
+            ]
+        );
+        let upgraded = fs::read_to_string(&services_path).unwrap();
+        assert!(!upgraded.contains("keep_alive"));
+        assert!(services_migration_backup_path(&services_path, 2).exists());
+    }
+
+    #[test]
+    fn hibernation_policy_thresholds_and_clamping() {
+        assert_eq!(
+            HibernationPolicy::Default.idle_threshold_secs(10),
+            Some(600)
+        );
+        assert_eq!(HibernationPolicy::Default.idle_threshold_secs(0), None);
+        assert_eq!(HibernationPolicy::Never.idle_threshold_secs(10), None);
+        assert_eq!(
+            HibernationPolicy::After { minutes: 3 }.idle_threshold_secs(0),
+            Some(180)
+        );
+        assert_eq!(
+            HibernationPolicy::OnSwitchAway.idle_threshold_secs(10),
+            Some(0)
+        );
+
+        let json = r#"{"id":"a","name":"A","url":"https://a.example.com","icon":"x",
+            "hibernation":{"mode":"after","minutes":0}}"#;
+        let service: Service = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            normalize_service(service).hibernation,
+            HibernationPolicy::After { minutes: 1 }
+        );
+        let heavy = serde_json::to_value(HibernationPolicy::OnSwitchAway).unwrap();
+        assert_eq!(heavy, serde_json::json!({ "mode": "on_switch_away" }));
+    }
+
+    #[test]
+    fn saved_services_use_current_envelope() {
+        let dir = tempdir().expect("tempdir should be created");
+        save_services(dir.path(), &default_services()).unwrap();
+        let doc: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(get_services_path(dir.path())).unwrap())
+                .unwrap();
+        assert_eq!(doc["schema_version"], SERVICES_SCHEMA_VERSION);
+        assert_eq!(doc["services"].as_array().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn newer_schema_version_is_rejected_without_touching_file() {
+        let dir = tempdir().expect("tempdir should be created");
+        let services_path = get_services_path(dir.path());
+        let future = r#"{"schema_version": 99, "services": []}"#;
+        fs::write(&services_path, future).unwrap();
+
+        match load_services(dir.path()) {
+            Err(LoadServicesError::UnsupportedSchemaVersion { found }) => assert_eq!(found, 99),
+            other => panic!("expected unsupported version error, got {other:?}"),
+        }
+        assert_eq!(fs::read_to_string(&services_path).unwrap(), future);
+        assert!(!services_backup_path(&services_path).exists());
+    }
+
+    #[test]
+    fn newer_services_file_survives_a_save_attempt() {
+        let dir = tempdir().expect("tempdir should be created");
+        let services_path = get_services_path(dir.path());
+        let future = r#"{"schema_version": 99, "services": [{"id": "mail"}]}"#;
+        fs::write(&services_path, future).unwrap();
+
+        // The app runs with an empty list; saving it must not replace the file.
+        match save_services(dir.path(), &[named_service("chat", "Chat")]) {
+            Err(ConfigError::NewerServicesFile { found }) => assert_eq!(found, 99),
+            other => panic!("expected the save to be refused, got {other:?}"),
+        }
+        assert!(matches!(
+            rename_service_id(dir.path(), &[], "mail", "email"),
+            Err(ConfigError::NewerServicesFile { .. })
+        ));
+        assert_eq!(fs::read_to_string(&services_path).unwrap(), future);
+
+        // Once the file is replaced by one this version reads, saves work again.
+        fs::remove_file(&services_path).unwrap();
+        save_services(dir.path(), &[named_service("chat", "Chat")]).unwrap();
+    }
+
+    #[test]
+    fn services_migrations_form_a_contiguous_chain() {
+        for (i, step) in SERVICES_MIGRATIONS.iter().enumerate() {
+            assert_eq!(step.from, i as u64 + 1);
+        }
+        assert_eq!(
+            SERVICES_MIGRATIONS.len() as u64 + 1,
+            SERVICES_SCHEMA_VERSION
+        );
+        // An envelope without a version is not a layout we know.
+        assert!(parse_services_document(r#"{"services": []}"#).is_err());
+    }
+
+    fn named_service(id: &str, name: &str) -> Service {
+        Service {
+            id: id.to_string(),
+            name: name.to_string(),
+            ..service_with_notify(None)
+        }
+    }
+
+    #[test]
+    fn snapshots_rotate_and_skip_duplicates() {
+        let dir = tempdir().expect("tempdir should be created");
+        let app_data_dir = dir.path();
+
+        // Nothing to snapshot before the first save.
+        assert_eq!(snapshot_services_at(app_data_dir, 1000, 3).unwrap(), None);
+
+        for (i, ms) in [1000u64, 2000, 3000, 4000].iter().enumerate() {
+            let services: Vec<Service> = (0..=i)
+                .map(|n| named_service(&format!("s{n}"), "S"))
+                .collect();
+            save_services(app_data_dir, &services).unwrap();
+            assert!(snapshot_services_at(app_data_dir, *ms, 3)
+                .unwrap()
+                .is_some());
+        }
+        // Unchanged file: no new snapshot.
+        assert_eq!(snapshot_services_at(app_data_dir, 5000, 3).unwrap(), None);
+
+        let listed = list_services_snapshots(app_data_dir).unwrap();
+        let ids: Vec<&str> = listed.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, vec!["4000", "3000", "2000"]);
+        assert_eq!(listed[0].timestamp_ms, 4000);
+        assert_eq!(listed[0].service_count, Some(4));
+        assert_eq!(listed[2].service_count, Some(2));
+    }
+
+    #[test]
+    fn snapshot_ids_disambiguate_same_millisecond() {
+        let dir = tempdir().expect("tempdir should be created");
+        save_services(dir.path(), &[named_service("a", "A")]).unwrap();
+        assert_eq!(
+            snapshot_services_at(dir.path(), 7, 5).unwrap().as_deref(),
+            Some("7")
+        );
+        save_services(dir.path(), &[named_service("b", "B")]).unwrap();
+        assert_eq!(
+            snapshot_services_at(dir.path(), 7, 5).unwrap().as_deref(),
+            Some("7-1")
+        );
+        let ids = snapshot_ids(dir.path()).unwrap();
+        assert_eq!(ids, vec!["7", "7-1"]);
+    }
+
+    #[test]
+    fn restore_snapshot_is_undoable() {
+        let dir = tempdir().expect("tempdir should be created");
+        let app_data_dir = dir.path();
+        save_services(app_data_dir, &[named_service("a", "A")]).unwrap();
+        let good = snapshot_services(app_data_dir).unwrap().unwrap();
+        save_services(app_data_dir, &[named_service("b", "B")]).unwrap();
+
+        restore_services_snapshot(app_data_dir, &good).unwrap();
+        let loaded = load_services(app_data_dir).unwrap();
+        assert_eq!(loaded.services.len(), 1);
+        assert_eq!(loaded.services[0].id, "a");
+        // The state we restored over ("b") is now in the history too.
+        let listed = list_services_snapshots(app_data_dir).unwrap();
+        assert_eq!(listed.len(), 2);
+    }
+
+    #[test]
+    fn snapshots_carry_customizations() {
+        let dir = tempdir().expect("tempdir should be created");
+        let app_data_dir = dir.path();
+        let mut service = named_service("a", "A");
+        service.custom_css = Some("body { color: red }".to_string());
+        save_services(app_data_dir, &[service.clone()]).unwrap();
+        let styled = snapshot_services(app_data_dir).unwrap().unwrap();
+
+        // A customization-only change is still a new snapshot.
+        service.custom_css = Some("body { color: blue }".to_string());
+        save_services(app_data_dir, &[service]).unwrap();
+        assert!(snapshot_services(app_data_dir).unwrap().is_some());
+
+        let diff = diff_services_snapshot(app_data_dir, &styled).unwrap();
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].fields, vec!["custom_css"]);
+
+        restore_services_snapshot(app_data_dir, &styled).unwrap();
+        let loaded = load_services(app_data_dir).unwrap();
+        assert_eq!(
+            loaded.services[0].custom_css.as_deref(),
+            Some("body { color: red }")
+        );
+        let services_json = fs::read_to_string(get_services_path(app_data_dir)).unwrap();
+        assert!(!services_json.contains("custom_"));
+    }
+
+    #[test]
+    fn snapshot_ids_cannot_escape_history_dir() {
+        let dir = tempdir().expect("tempdir should be created");
+        for bad in ["", "../services", "1/../../x", "abc"] {
+            assert!(restore_services_snapshot(dir.path(), bad).is_err());
+            assert!(diff_services_snapshot(dir.path(), bad).is_err());
+        }
+    }
+
+    #[test]
+    fn diff_services_reports_changes() {
+        let current = vec![
+            named_service("a", "A"),
+            named_service("b", "B"),
+            named_service("c", "C"),
+        ];
+        let mut b2 = named_service("b", "B");
+        b2.url = "https://other.example.com".to_string();
+        b2.zoom = Some(1.5);
+        let target = vec![named_service("c", "C"), b2, named_service("d", "D")];
+
+        let diff = diff_services(&current, &target);
+        assert_eq!(
+            diff.added,
+            vec![ServiceRef {
+                id: "d".to_string(),
+                name: "D".to_string()
+            }]
+        );
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].id, "a");
+        assert_eq!(diff.changed.len(), 1);
+        let mut fields = diff.changed[0].fields.clone();
+        fields.sort();
+        assert_eq!(fields, vec!["url", "zoom"]);
+        assert!(diff.reordered);
+
+        assert_eq!(diff_services(&current, &current), ServicesDiff::default());
+
+        // A field left out of the target's JSON (unset) is a change as well.
+        let mut styled = named_service("c", "C");
+        styled.custom_css = Some("body { color: red }".to_string());
+        let diff = diff_services(&[styled], &[named_service("c", "C")]);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].fields, vec!["custom_css"]);
+    }
+
+    #[test]
+    fn validate_services_reports_every_problem() {
+        let mut dup = named_service("mail", "Mail 2");
+        dup.url = "javascript:alert(1)".to_string();
+        let services = vec![
+            named_service("mail", "Mail"),
+            dup,
+            named_service("../evil", "Evil"),
+            named_service("c:drive", "  "),
+            named_service("", "No id"),
+        ];
+        let problems: Vec<(usize, &str, &str)> = validate_services(&services)
+            .iter()
+            .map(|e| (e.index, e.field, e.code))
+            .collect();
+        assert_eq!(
+            problems,
+            vec![
+                (1, "id", "duplicate"),
+                (1, "url", "invalid_url"),
+                (2, "id", "invalid_id"),
+                (3, "id", "invalid_id"),
+                (3, "name", "empty"),
+                (4, "id", "empty"),
+            ]
+        );
+        assert!(validate_services(&default_services()).is_empty());
+    }
+
+    #[test]
+    fn generated_ids_are_slugs_unique_in_the_list() {
+        let existing = vec![
+            named_service("gmail", "Gmail"),
+            named_service("gmail-2", "Gmail"),
+        ];
+        assert_eq!(generate_service_id("Gmail", &existing), "gmail-3");
+        assert_eq!(
+            generate_service_id("  Mon Équipe / Slack!", &[]),
+            "mon-quipe-slack"
+        );
+        assert_eq!(generate_service_id("../..", &[]), "service");
+        assert!(is_safe_service_id(&generate_service_id("a\\b:c", &[])));
+        // The app's own labels are never handed out.
+        assert_eq!(generate_service_id("Settings", &[]), "settings-2");
+        assert_eq!(generate_service_id("main", &[]), "main-2");
+    }
+
+    #[test]
+    fn reserved_ids_are_rejected() {
+        for id in RESERVED_SERVICE_IDS {
+            let problems = validate_services(&[named_service(id, "Clash")]);
+            assert_eq!(problems.len(), 1, "{id:?} should be rejected");
+            assert_eq!(problems[0].code, "reserved_id");
+        }
+    }
+
+    #[test]
+    fn ids_are_limited_to_label_characters() {
+        for id in ["mail", "Mail_2", "my-mail", "V1StGXR8_Z"] {
+            assert!(is_safe_service_id(id), "{id:?} should be accepted");
+        }
+        // Not valid webview labels, even if they'd make fine directory names.
+        for id in ["my mail", "a.b", "", ".", "..", "a/b", "é"] {
+            assert!(!is_safe_service_id(id), "{id:?} should be rejected");
+        }
+        let problems = validate_services(&[named_service("my mail", "Mail")]);
+        assert_eq!(problems.len(), 1);
+        assert_eq!((problems[0].field, problems[0].code), ("id", "invalid_id"));
+    }
+
+    #[test]
+    fn retry_with_backoff_retries_transient_errors_only() {
+        let mut attempts = 0;
+        let result = retry_with_backoff(&[1, 1, 1], || {
+            attempts += 1;
+            if attempts < 3 {
+                Err(io::Error::from(io::ErrorKind::PermissionDenied))
+            } else {
+                Ok(attempts)
+            }
+        });
+        assert_eq!(result.unwrap(), 3);
+
+        let mut attempts = 0;
+        let result: io::Result<()> = retry_with_backoff(&[1, 1], || {
+            attempts += 1;
+            Err(io::Error::from(io::ErrorKind::PermissionDenied))
+        });
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::PermissionDenied);
+        assert_eq!(attempts, 3, "one try plus one per delay");
+
+        let mut attempts = 0;
+        let result: io::Result<()> = retry_with_backoff(&[1, 1], || {
+            attempts += 1;
+            Err(io::Error::from(io::ErrorKind::NotFound))
+        });
+        assert!(result.is_err());
+        assert_eq!(attempts, 1);
+    }
+
+    #[test]
+    fn rename_service_id_moves_session_and_state() {
+        let dir = tempdir().expect("tempdir should be created");
+        let old_session = get_webview_data_dir(dir.path(), "old");
+        fs::create_dir_all(&old_session).unwrap();
+        fs::write(old_session.join("Cookies"), "session").unwrap();
+        let page = LastPage {
+            url: "https://mail.example.com/#inbox".to_string(),
+            home: "https://mail.example.com".to_string(),
+            scroll_y: 120,
+        };
+        save_state(
+            dir.path(),
+            &AppState {
+                last_active_service: Some("old".to_string()),
+                last_pages: HashMap::from([("old".to_string(), page.clone())]),
+            },
+        )
+        .unwrap();
+
+        let renamed = vec![named_service("new", "Mail")];
+        rename_service_id(dir.path(), &renamed, "old", "new").unwrap();
+
+        assert!(!old_session.exists());
+        assert_eq!(
+            fs::read_to_string(get_webview_data_dir(dir.path(), "new").join("Cookies")).unwrap(),
+            "session"
+        );
+        let state = load_state(dir.path());
+        assert_eq!(state.last_active_service.as_deref(), Some("new"));
+        assert_eq!(state.last_pages, HashMap::from([("new".to_string(), page)]));
+        let saved = load_services(dir.path()).unwrap().services;
+        assert_eq!(saved[0].id, "new");
+
+        // Never merge into another service's session.
+        fs::create_dir_all(get_webview_data_dir(dir.path(), "other")).unwrap();
+        let renamed = vec![named_service("other", "Mail")];
+        assert!(rename_service_id(dir.path(), &renamed, "new", "other").is_err());
+        assert!(get_webview_data_dir(dir.path(), "new")
+            .join("Cookies")
+            .exists());
+    }
+
+    #[test]
+    fn profiles_have_separate_dirs_and_guard_deletion() {
+        let root = tempdir().expect("tempdir should be created");
+        assert_eq!(
+            active_profile_dir(root.path()),
+            (DEFAULT_PROFILE_ID.to_string(), root.path().to_path_buf())
+        );
+
+        let work = create_profile(root.path(), "Work").unwrap();
+        let work_2 = create_profile(root.path(), "work").unwrap();
+        assert_eq!((work.id.as_str(), work_2.id.as_str()), ("work", "work-2"));
+        assert!(create_profile(root.path(), "  ").is_err());
+
+        let work_dir = set_active_profile(root.path(), "work").unwrap();
+        assert_eq!(work_dir, root.path().join("profiles").join("work"));
+        assert_eq!(active_profile_dir(root.path()).1, work_dir);
+        save_services(&work_dir, &[named_service("w", "Work mail")]).unwrap();
+        // The default profile's files stay in the root, untouched.
+        assert!(!get_services_path(root.path()).exists());
+
+        assert!(delete_profile(root.path(), "work").is_err(), "active");
+        assert!(delete_profile(root.path(), DEFAULT_PROFILE_ID).is_err());
+        assert!(set_active_profile(root.path(), "nope").is_err());
+        set_active_profile(root.path(), DEFAULT_PROFILE_ID).unwrap();
+        delete_profile(root.path(), "work").unwrap();
+        assert!(!work_dir.exists());
+        let ids: Vec<String> = list_profiles(root.path())
+            .profiles
+            .into_iter()
+            .map(|p| p.id)
+            .collect();
+        assert_eq!(ids, vec!["default", "work-2"]);
+    }
+
+    #[test]
+    fn blank_profile_ids_are_ignored() {
+        let root = tempdir().expect("tempdir should be created");
+        let work = create_profile(root.path(), "Work").unwrap();
+        // Hand-edited profiles.json: a blank id would resolve to `profiles/`.
+        fs::write(
+            get_profiles_path(root.path()),
+            r#"{"profiles": [{"id": "", "name": "Empty"}, {"id": " ", "name": "Blank"}, {"id": "work", "name": "Work"}]}"#,
+        )
+        .unwrap();
+
+        let ids: Vec<String> = list_profiles(root.path())
+            .profiles
+            .into_iter()
+            .map(|p| p.id)
+            .collect();
+        assert_eq!(ids, vec!["default", "work"]);
+        assert!(delete_profile(root.path(), "").is_err());
+        assert!(delete_profile(root.path(), " ").is_err());
+        assert!(get_profile_dir(root.path(), &work.id).exists());
+    }
+
+    #[test]
+    fn customizations_are_stored_next_to_the_session() {
+        let dir = tempdir().expect("tempdir should be created");
+        let mut service = named_service("mail", "Mail");
+        service.custom_css = Some(".upsell { display: none }".to_string());
+        service.custom_js = Some("console.log('hi')".to_string());
+        service.notify_direct_only = true;
+        save_services(dir.path(), &[service]).unwrap();
+
+        let services_json = fs::read_to_string(get_services_path(dir.path())).unwrap();
+        assert!(!services_json.contains("custom_"));
+        let session = get_webview_data_dir(dir.path(), "mail");
+        assert_eq!(
+            fs::read_to_string(session.join("custom.css")).unwrap(),
+            ".upsell { display: none }"
+        );
+
+        let mut loaded = load_services(dir.path()).unwrap().services;
+        assert_eq!(loaded[0].custom_js.as_deref(), Some("console.log('hi')"));
+        // Only the customizations are moved out, other settings stay.
+        assert!(loaded[0].notify_direct_only);
+
+        // Clearing a field removes its file; blank counts as cleared.
+        loaded[0].custom_css = None;
+        loaded[0].custom_js = Some("  ".to_string());
+        let (loaded, _) = sanitize_services(loaded);
+        save_services(dir.path(), &loaded).unwrap();
+        assert!(!session.join("custom.css").exists());
+        assert!(!session.join("custom.js").exists());
+    }
+
+    #[test]
+    fn malformed_entries_are_skipped_not_fatal() {
+        let dir = tempdir().expect("tempdir should be created");
+        let services_path = get_services_path(dir.path());
+        let content = r#"{"schema_version": 3, "services": [
+            {"id":"ok","name":"Ok","url":"https://example.com","icon":"x"},
+            {"id":"bad-zoom","name":"Z","url":"https://z.example.com","icon":"x","zoom":"big"},
+            {"name":"No id","url":"https://n.example.com","icon":"x"},
+            42,
+            {"id":"ok-2","name":"Ok 2","url":"https://two.example.com","icon":"x"}
+        ]}"#;
+        fs::write(&services_path, content).unwrap();
+        // Backup of an earlier, fully corrupted file: must survive.
+        let corrupted_backup = services_backup_path(&services_path);
+        fs::write(&corrupted_backup, "{not json").unwrap();
+
+        let loaded = load_services(dir.path()).expect("valid entries should still load");
+        let ids: Vec<&str> = loaded.services.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, vec!["ok", "ok-2"]);
+
+        let skipped: Vec<(usize, Option<&str>)> = loaded
+            .skipped_entries
+            .iter()
+            .map(|e| (e.index, e.id.as_deref()))
+            .collect();
+        assert_eq!(skipped, vec![(1, Some("bad-zoom")), (2, None), (3, None)]);
+        assert!(loaded.skipped_entries[0].error.contains("invalid type"));
+
+        // The file stays in place; an intact copy is kept for manual recovery.
+        assert_eq!(fs::read_to_string(&services_path).unwrap(), content);
+        let partial_backup = services_partial_backup_path(&services_path);
+        assert_eq!(fs::read_to_string(&partial_backup).unwrap(), content);
+        assert_eq!(fs::read_to_string(&corrupted_backup).unwrap(), "{not json");
+
+        // Reloading the same file leaves the copy alone.
+        let modified = fs::metadata(&partial_backup).unwrap().modified().unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        load_services(dir.path()).unwrap();
+        assert_eq!(
+            fs::metadata(&partial_backup).unwrap().modified().unwrap(),
+            modified
+        );
+    }
+
     #[test]
     fn test_save_services_propagates_io_error() {
         let dir = tempdir().expect("tempdir should be created");
@@ -616,6 +2574,166 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn interrupted_write_keeps_previous_services_file() {
+        let dir = tempdir().expect("tempdir should be created");
+        let app_data_dir = dir.path();
+        let services_path = get_services_path(app_data_dir);
+        let services = default_services();
+        save_services(app_data_dir, &services).expect("initial save should succeed");
+        let before = fs::read_to_string(&services_path).expect("services.json should exist");
+
+        // Simulate a crash after the scratch file was (partially) written but
+        // before the rename: the real file must be untouched and still load.
+        let tmp = write_temp_file(&services_path, b"[{\"id\":\"trunc").unwrap();
+        assert!(tmp.exists());
+        assert_eq!(fs::read_to_string(&services_path).unwrap(), before);
+        let loaded = load_services(app_data_dir).expect("previous file should still load");
+        assert_eq!(loaded.services.len(), services.len());
+        assert!(!services_backup_path(&services_path).exists());
+
+        // A leftover scratch file from the crash doesn't block the next save
+        // (which uses a scratch file of its own).
+        save_services(app_data_dir, &services[..1]).expect("save after crash should succeed");
+        let reloaded = load_services(app_data_dir).unwrap();
+        assert_eq!(reloaded.services.len(), 1);
+    }
+
+    #[test]
+    fn stale_scratch_files_are_removed_at_startup() {
+        let dir = tempdir().expect("tempdir should be created");
+        let services_path = get_services_path(dir.path());
+        save_services(dir.path(), &default_services()).unwrap();
+        // Left by a crashed instance, by this one, and by someone else.
+        let stale = dir.path().join("services.json.4294967295.3.tmp");
+        fs::write(&stale, b"[{\"id\":\"trunc").unwrap();
+        let own = write_temp_file(&services_path, b"[]").unwrap();
+        let unrelated = dir.path().join("notes.tmp");
+        fs::write(&unrelated, b"").unwrap();
+
+        remove_stale_temp_files(dir.path());
+        assert!(!stale.exists());
+        assert!(own.exists());
+        assert!(unrelated.exists());
+        assert!(services_path.exists());
+    }
+
+    #[test]
+    fn concurrent_writes_of_one_file_do_not_collide() {
+        let dir = tempdir().expect("tempdir should be created");
+        let path = dir.path().join("state.json");
+        let writers: Vec<_> = (0..8)
+            .map(|i| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..20 {
+                        write_atomic(&path, format!("{{\"writer\": {i}}}").as_bytes())?;
+                    }
+                    io::Result::Ok(())
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap().expect("no write should fail");
+        }
+        // Some writer's full content won, and no scratch file is left behind.
+        let content: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert!(content["writer"].is_u64());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn last_page_is_taken_once_and_only_for_the_same_home() {
+        let dir = tempdir().unwrap();
+        let page = |url: &str, home: &str| LastPage {
+            url: url.to_string(),
+            home: home.to_string(),
+            scroll_y: 640,
+        };
+        update_state(dir.path(), |state| {
+            state.last_active_service = Some("mail".to_string());
+            state.last_pages.insert(
+                "mail".to_string(),
+                page(
+                    "https://mail.example.com/#inbox/42",
+                    "https://mail.example.com",
+                ),
+            );
+            state.last_pages.insert(
+                "wiki".to_string(),
+                page("https://old.example.com/page", "https://old.example.com"),
+            );
+        })
+        .unwrap();
+
+        let mail = take_last_page(dir.path(), "mail", "https://mail.example.com").unwrap();
+        assert_eq!(
+            (mail.url.as_str(), mail.scroll_y),
+            ("https://mail.example.com/#inbox/42", 640)
+        );
+        assert_eq!(
+            take_last_page(dir.path(), "mail", "https://mail.example.com"),
+            None
+        );
+        // The service was pointed elsewhere: the page is dropped.
+        assert_eq!(
+            take_last_page(dir.path(), "wiki", "https://new.example.com"),
+            None
+        );
+        let state = load_state(dir.path());
+        assert!(state.last_pages.is_empty());
+        assert_eq!(state.last_active_service.as_deref(), Some("mail"));
+
+        // Older state.json files have no pages.
+        fs::write(
+            dir.path().join("state.json"),
+            r#"{"last_active_service":null}"#,
+        )
+        .unwrap();
+        assert!(load_state(dir.path()).last_pages.is_empty());
+        // Pages recorded before scroll positions were kept reopen at the top.
+        fs::write(
+            dir.path().join("state.json"),
+            r#"{"last_active_service":null,"last_pages":{"mail":{"url":"https://mail.example.com/#inbox","home":"https://mail.example.com"}}}"#,
+        )
+        .unwrap();
+        assert_eq!(load_state(dir.path()).last_pages["mail"].scroll_y, 0);
+    }
+
+    #[test]
+    fn failed_commit_keeps_previous_state_and_preferences() {
+        let dir = tempdir().expect("tempdir should be created");
+        let app_data_dir = dir.path();
+        save_state(
+            app_data_dir,
+            &AppState {
+                last_active_service: Some("before".to_string()),
+                ..AppState::default()
+            },
+        )
+        .unwrap();
+        let prefs = Preferences {
+            icon_size: 64,
+            ..Preferences::default()
+        };
+        save_preferences(app_data_dir, &prefs).unwrap();
+
+        // The rename never happens (its source vanished, as when the process
+        // dies between the two steps): nothing visible changes.
+        let state_path = app_data_dir.join("state.json");
+        let missing = app_data_dir.join("state.json.tmp");
+        assert!(commit_temp_file(&missing, &state_path).is_err());
+        assert_eq!(
+            load_state(app_data_dir).last_active_service.as_deref(),
+            Some("before")
+        );
+
+        let prefs_path = app_data_dir.join("preferences.json");
+        write_temp_file(&prefs_path, b"{\"icon_size\": 2").unwrap();
+        assert_eq!(load_preferences(app_data_dir).icon_size, 64);
+    }
+
     #[test]
     fn test_load_preferences() {
         let dir = tempdir().expect("tempdir should be created");
@@ -626,6 +2744,7 @@ mod tests {
         fs::create_dir_all(&app_data_dir).expect("app data dir should be created");
         fs::write(&prefs_path, "").expect("empty preferences.json should be written");
         let empty = load_preferences(&app_data_dir);
+        assert!(parse_preferences("").is_err());
         assert_eq!(empty.icon_size, 40);
         assert_eq!(empty.accent_color, "blue");
         assert_eq!(empty.theme, "dark");
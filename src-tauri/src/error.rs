@@ -19,11 +19,49 @@ pub enum TauriumError {
     #[error(transparent)]
     Serialization(#[from] serde_json::Error),
     #[error(transparent)]
-    Config(#[from] crate::config::ConfigError),
+    Config(crate::config::ConfigError),
     #[error(transparent)]
     LoadServices(#[from] crate::config::LoadServicesError),
     #[error("{0}")]
     Certificate(String),
+    #[error(transparent)]
+    Workspace(crate::workspace::WorkspaceError),
+    #[error("Invalid services: {}", format_problems(.0))]
+    Validation(Vec<crate::config::ServiceFieldError>),
+    /// services.json comes from a newer Taurium: the whole file is left
+    /// alone, whatever the services being saved.
+    #[error("{}", crate::config::ConfigError::NewerServicesFile { found: *found })]
+    NewerConfig { found: u64 },
+}
+
+impl From<crate::config::ConfigError> for TauriumError {
+    fn from(err: crate::config::ConfigError) -> Self {
+        match err {
+            crate::config::ConfigError::NewerServicesFile { found } => {
+                TauriumError::NewerConfig { found }
+            }
+            err => TauriumError::Config(err),
+        }
+    }
+}
+
+impl From<crate::workspace::WorkspaceError> for TauriumError {
+    fn from(err: crate::workspace::WorkspaceError) -> Self {
+        match err {
+            crate::workspace::WorkspaceError::Validation(problems) => {
+                TauriumError::Validation(problems)
+            }
+            err => TauriumError::Workspace(err),
+        }
+    }
+}
+
+fn format_problems(problems: &[crate::config::ServiceFieldError]) -> String {
+    problems
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
 }
 
 impl Serialize for TauriumError {
@@ -31,7 +69,14 @@ impl Serialize for TauriumError {
     where
         S: serde::Serializer,
     {
-        let mut state = serializer.serialize_struct("TauriumError", 2)?;
+        // Validation errors also carry their per-field problems, so the
+        // frontend can highlight them without parsing the message.
+        let problems = match self {
+            TauriumError::Validation(problems) => Some(problems),
+            _ => None,
+        };
+        let mut state =
+            serializer.serialize_struct("TauriumError", 2 + usize::from(problems.is_some()))?;
         state.serialize_field(
             "type",
             match self {
@@ -45,9 +90,15 @@ impl Serialize for TauriumError {
                 TauriumError::Config(_) => "Config",
                 TauriumError::LoadServices(_) => "LoadServices",
                 TauriumError::Certificate(_) => "Certificate",
+                TauriumError::Workspace(_) => "Workspace",
+                TauriumError::Validation(_) => "Validation",
+                TauriumError::NewerConfig { .. } => "NewerConfig",
             },
         )?;
         state.serialize_field("message", &self.to_string())?;
+        if let Some(problems) = problems {
+            state.serialize_field("problems", problems)?;
+        }
         state.end()
     }
 }
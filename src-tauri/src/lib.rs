@@ -1,19 +1,24 @@
 mod cert_trust;
 mod config;
 mod error;
+mod memory_monitor;
+mod notification_history;
+mod notifications;
+mod notify_schedule;
+mod portable;
+mod quiet_hours;
 mod recipes;
+mod watcher;
 mod webviews;
+mod workspace;
 
-use config::{
-    load_preferences, load_services, load_state, save_state, AppState, Preferences, Service,
-    ServicesLoadInfo,
-};
+use config::{load_preferences, load_services, load_state, Preferences, Service, ServicesLoadInfo};
 use error::TauriumError;
 use recipes::Recipe;
 use std::collections::{HashMap, HashSet};
 use tauri::menu::{ContextMenu, MenuBuilder, MenuItemBuilder};
 use tauri::{LogicalPosition, LogicalSize, Manager, WebviewUrl};
-use webviews::WebviewState;
+use webviews::{BadgeCount, WebviewState};
 
 const TAURI_INVOKE_SHIM: &str = r#"
 if (!window.__TAURI__ && window.__TAURI_INTERNALS__ && typeof window.__TAURI_INTERNALS__.invoke === 'function') {
@@ -28,6 +33,7 @@ if (!window.__TAURI__ && window.__TAURI_INTERNALS__ && typeof window.__TAURI_INT
 #[derive(serde::Serialize)]
 pub struct ApplyServicesResponse {
     pub filtered_url_count: usize,
+    pub skipped_entries: Vec<config::SkippedServiceEntry>,
 }
 
 #[tauri::command]
@@ -54,17 +60,40 @@ fn switch_service(
 ) -> Result<(), TauriumError> {
     webviews::switch_to(&app, &state, &id)?;
 
-    let app_state = AppState {
-        last_active_service: Some(id),
-    };
-    save_state(&state.app_data_dir, &app_state)?;
+    // Read-modify-write: state.json also holds the hibernated pages.
+    config::update_state(&webviews::current_data_dir(&state), |app_state| {
+        app_state.last_active_service = Some(id);
+    })?;
 
     Ok(())
 }
 
+#[tauri::command]
+fn generate_service_id(
+    state: tauri::State<WebviewState>,
+    name: String,
+) -> Result<String, TauriumError> {
+    let services = state
+        .services
+        .lock()
+        .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))?;
+    Ok(config::generate_service_id(&name, &services))
+}
+
+// `async`: closes (and may recreate) the service webview via run_on_main_thread.
+#[tauri::command(async)]
+fn rename_service_id(
+    app: tauri::AppHandle,
+    state: tauri::State<WebviewState>,
+    old_id: String,
+    new_id: String,
+) -> Result<(), TauriumError> {
+    webviews::rename_service(&app, &state, &old_id, new_id.trim())
+}
+
 #[tauri::command]
 fn get_last_active_service(state: tauri::State<WebviewState>) -> Option<String> {
-    let app_state = load_state(&state.app_data_dir);
+    let app_state = load_state(&webviews::current_data_dir(&state));
     app_state.last_active_service
 }
 
@@ -73,7 +102,17 @@ fn save_services_cmd(
     state: tauri::State<WebviewState>,
     services: Vec<Service>,
 ) -> Result<(), TauriumError> {
-    config::save_services(&state.app_data_dir, &services)?;
+    let problems = config::validate_services(&services);
+    if !problems.is_empty() {
+        return Err(TauriumError::Validation(problems));
+    }
+    // Keep the previous file in the rolling history so this save can be
+    // undone from the settings page. Never blocks the save itself.
+    let app_data_dir = webviews::current_data_dir(&state);
+    if let Err(err) = config::snapshot_services(&app_data_dir) {
+        eprintln!("[Taurium] Failed to snapshot services.json: {err}");
+    }
+    config::save_services(&app_data_dir, &services)?;
     {
         let mut stored = state
             .services
@@ -111,7 +150,7 @@ fn reload_service(
 #[tauri::command]
 fn get_badge_counts(
     state: tauri::State<WebviewState>,
-) -> Result<HashMap<String, u32>, TauriumError> {
+) -> Result<HashMap<String, BadgeCount>, TauriumError> {
     let badge_counts = state
         .badge_counts
         .lock()
@@ -119,6 +158,94 @@ fn get_badge_counts(
     Ok(badge_counts.clone())
 }
 
+/// Called by the recipe badge script injected in service webviews; the
+/// calling webview's label is the service id.
+#[tauri::command]
+fn report_badge_count(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    direct: u32,
+    indirect: Option<u32>,
+) {
+    let count = BadgeCount {
+        direct,
+        indirect: indirect.unwrap_or(0),
+    };
+    webviews::handle_badge_report(&app, webview.label(), count);
+}
+
+/// Called by the scroll reporter injected in service webviews; the calling
+/// webview's label is the service id.
+#[tauri::command]
+fn report_scroll_position(app: tauri::AppHandle, webview: tauri::Webview, url: String, y: u32) {
+    webviews::handle_scroll_report(&app, webview.label(), url, y);
+}
+
+/// Called by the Notification shim injected in service webviews.
+#[tauri::command]
+fn forward_web_notification(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    notification: notifications::WebNotification,
+) {
+    notifications::handle_web_notification(&app, webview.label(), notification);
+}
+
+/// Hold notifications back for `minutes` (at least one); returns the end of
+/// the snooze (Unix time in milliseconds).
+#[tauri::command]
+fn snooze_notifications(app: tauri::AppHandle, minutes: u32) -> u64 {
+    let duration = std::time::Duration::from_secs(u64::from(minutes.max(1)) * 60);
+    unix_millis(notifications::snooze(&app, duration))
+}
+
+#[tauri::command]
+fn unsnooze_notifications(app: tauri::AppHandle) {
+    notifications::unsnooze(&app);
+}
+
+/// End of the running snooze (Unix time in milliseconds), if any.
+#[tauri::command]
+fn get_snooze_until(app: tauri::AppHandle) -> Option<u64> {
+    notifications::snoozed_until(&app).map(unix_millis)
+}
+
+/// Recent activity, newest first (see `notification_history.rs`).
+#[tauri::command]
+fn get_notification_history(
+    state: tauri::State<WebviewState>,
+) -> Vec<notification_history::NotificationRecord> {
+    notification_history::load_history(&webviews::current_data_dir(&state))
+}
+
+#[tauri::command]
+fn clear_notification_history(state: tauri::State<WebviewState>) -> Result<(), TauriumError> {
+    notification_history::clear_history(&webviews::current_data_dir(&state))?;
+    Ok(())
+}
+
+/// Memory and CPU time per service, with its load status. Async: it reads
+/// every process of Taurium's tree.
+#[tauri::command(async)]
+fn get_service_resource_usage(
+    state: tauri::State<WebviewState>,
+) -> Result<webviews::ResourceUsageReport, TauriumError> {
+    webviews::service_resource_usage(&state)
+}
+
+/// Switch to a service from the recent activity list. Async for the same
+/// reason as `switch_service`.
+#[tauri::command(async)]
+fn focus_service(app: tauri::AppHandle, id: String) {
+    notifications::focus_service(&app, &id);
+}
+
+fn unix_millis(time: std::time::SystemTime) -> u64 {
+    time.duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
 #[tauri::command]
 fn get_service_url(
     state: tauri::State<WebviewState>,
@@ -140,13 +267,182 @@ fn apply_services(
     app: tauri::AppHandle,
     state: tauri::State<WebviewState>,
 ) -> Result<ApplyServicesResponse, TauriumError> {
-    let loaded = load_services(&state.app_data_dir)?;
+    let loaded = load_services(&webviews::current_data_dir(&state))?;
+    webviews::apply_service_changes(&app, &state, loaded.services)?;
+    Ok(ApplyServicesResponse {
+        filtered_url_count: loaded.filtered_url_count,
+        skipped_entries: loaded.skipped_entries,
+    })
+}
+
+#[tauri::command]
+fn list_services_snapshots(
+    state: tauri::State<WebviewState>,
+) -> Result<Vec<config::ServicesSnapshot>, TauriumError> {
+    Ok(config::list_services_snapshots(
+        &webviews::current_data_dir(&state),
+    )?)
+}
+
+#[tauri::command]
+fn diff_services_snapshot(
+    state: tauri::State<WebviewState>,
+    id: String,
+) -> Result<config::ServicesDiff, TauriumError> {
+    Ok(config::diff_services_snapshot(
+        &webviews::current_data_dir(&state),
+        &id,
+    )?)
+}
+
+// `async` for the same reason as apply_services: restoring may add webviews.
+#[tauri::command(async)]
+fn restore_services_snapshot(
+    app: tauri::AppHandle,
+    state: tauri::State<WebviewState>,
+    id: String,
+) -> Result<ApplyServicesResponse, TauriumError> {
+    let app_data_dir = webviews::current_data_dir(&state);
+    config::restore_services_snapshot(&app_data_dir, &id)?;
+    eprintln!("[Taurium] Restored services.json snapshot {id}");
+    let loaded = load_services(&app_data_dir)?;
     webviews::apply_service_changes(&app, &state, loaded.services)?;
     Ok(ApplyServicesResponse {
         filtered_url_count: loaded.filtered_url_count,
+        skipped_entries: loaded.skipped_entries,
     })
 }
 
+// `async`: zipping session directories can take a while.
+#[tauri::command(async)]
+fn export_workspace(
+    app: tauri::AppHandle,
+    state: tauri::State<WebviewState>,
+    path: Option<String>,
+    include_webview_data: bool,
+) -> Result<workspace::ExportReport, TauriumError> {
+    let now_ms = config::now_unix_ms();
+    let dest = match path {
+        Some(p) if !p.trim().is_empty() => std::path::PathBuf::from(p.trim()),
+        _ => app
+            .path()
+            .download_dir()?
+            .join(format!("taurium-workspace-{now_ms}.zip")),
+    };
+    let report = workspace::export_workspace(
+        &webviews::current_data_dir(&state),
+        &dest,
+        include_webview_data,
+        &app.package_info().version.to_string(),
+        now_ms,
+    )?;
+    eprintln!(
+        "[Taurium] Workspace exported to {} ({} services, {} files skipped)",
+        report.path.display(),
+        report.service_count,
+        report.skipped_files.len()
+    );
+    Ok(report)
+}
+
+// `async` for the same reason as apply_services: importing adds webviews.
+#[tauri::command(async)]
+fn import_workspace(
+    app: tauri::AppHandle,
+    state: tauri::State<WebviewState>,
+    path: String,
+    options: workspace::ImportOptions,
+) -> Result<workspace::ImportReport, TauriumError> {
+    let existing = state
+        .services
+        .lock()
+        .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))?
+        .clone();
+    let (report, merged) = workspace::import_workspace(
+        &webviews::current_data_dir(&state),
+        std::path::Path::new(path.trim()),
+        &existing,
+        &options,
+    )?;
+    eprintln!(
+        "[Taurium] Workspace imported from {} ({} added, {} renamed, {} skipped)",
+        path,
+        report.added.len(),
+        report.renamed.len(),
+        report.skipped.len()
+    );
+    webviews::apply_service_changes(&app, &state, merged)?;
+    if report.preferences_imported {
+        let prefs = load_preferences(&webviews::current_data_dir(&state));
+        let prefs_json = serde_json::to_string(&prefs)?;
+        webviews::set_preferences(&state, prefs)?;
+        if let Some(sidebar) = app.get_webview("sidebar") {
+            sidebar
+                .eval(format!(
+                    "window.__applyPreferences && window.__applyPreferences({prefs_json})"
+                ))
+                .ok();
+        }
+    }
+    Ok(report)
+}
+
+#[tauri::command]
+fn list_profiles(state: tauri::State<WebviewState>) -> config::ProfilesInfo {
+    config::list_profiles(&state.data_root)
+}
+
+#[tauri::command]
+fn create_profile(
+    state: tauri::State<WebviewState>,
+    name: String,
+) -> Result<config::Profile, TauriumError> {
+    let profile = config::create_profile(&state.data_root, &name)?;
+    eprintln!("[Taurium] Created profile '{}'", profile.id);
+    Ok(profile)
+}
+
+// `async`: tears down every service webview via run_on_main_thread.
+#[tauri::command(async)]
+fn switch_profile(
+    app: tauri::AppHandle,
+    state: tauri::State<WebviewState>,
+    id: String,
+) -> Result<ServicesLoadInfo, TauriumError> {
+    let old_dir = webviews::current_data_dir(&state);
+    let new_dir = config::find_profile_dir(&state.data_root, &id)?;
+    if new_dir == old_dir {
+        return Ok(ServicesLoadInfo::default());
+    }
+    // Persisted first, so the profile shown is always the one the next launch
+    // opens; put back if the switch itself fails.
+    let (previous_id, _) = config::active_profile_dir(&state.data_root);
+    config::set_active_profile(&state.data_root, &id)?;
+    let info = match webviews::switch_profile(&app, &state, new_dir.clone()) {
+        Ok(info) => info,
+        Err(err) => {
+            if let Err(e) = config::set_active_profile(&state.data_root, &previous_id) {
+                eprintln!("[Taurium] Failed to restore active profile '{previous_id}': {e}");
+            }
+            return Err(err);
+        }
+    };
+    if let Some(watcher) = app.try_state::<watcher::ConfigWatcher>() {
+        if let Err(err) = watcher.rewatch(&old_dir, &new_dir) {
+            eprintln!("[Taurium] Failed to watch profile '{id}': {err}");
+        }
+    }
+    eprintln!("[Taurium] Switched to profile '{id}'");
+    Ok(info)
+}
+
+#[tauri::command]
+fn delete_profile(state: tauri::State<WebviewState>, id: String) -> Result<(), TauriumError> {
+    config::delete_profile(&state.data_root, &id)?;
+    eprintln!("[Taurium] Deleted profile '{id}'");
+    Ok(())
+}
+
 #[tauri::command]
 fn get_services_load_info(state: tauri::State<WebviewState>) -> ServicesLoadInfo {
     state.services_load_info.clone()
@@ -199,7 +495,7 @@ fn show_service_context_menu(app: tauri::AppHandle, id: String) -> Result<(), Ta
 
 #[tauri::command]
 fn get_preferences(state: tauri::State<WebviewState>) -> Preferences {
-    load_preferences(&state.app_data_dir)
+    webviews::current_preferences(&state)
 }
 
 #[tauri::command]
@@ -208,8 +504,9 @@ fn save_preferences_cmd(
     state: tauri::State<WebviewState>,
     prefs: Preferences,
 ) -> Result<String, TauriumError> {
-    config::save_preferences(&state.app_data_dir, &prefs)?;
+    config::save_preferences(&webviews::current_data_dir(&state), &prefs)?;
     let prefs_json = serde_json::to_string(&prefs)?;
+    webviews::set_preferences(&state, prefs)?;
 
     let sidebar = app
         .get_webview("sidebar")
@@ -232,9 +529,15 @@ fn set_sidebar_expanded(
     state: tauri::State<WebviewState>,
     expanded: bool,
 ) -> Result<(), TauriumError> {
-    let mut prefs = config::load_preferences(&state.app_data_dir);
-    prefs.sidebar_expanded = expanded;
-    if let Err(err) = config::save_preferences(&state.app_data_dir, &prefs) {
+    let prefs = {
+        let mut prefs = state
+            .preferences
+            .lock()
+            .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))?;
+        prefs.sidebar_expanded = expanded;
+        prefs.clone()
+    };
+    if let Err(err) = config::save_preferences(&webviews::current_data_dir(&state), &prefs) {
         eprintln!("[Taurium] Failed to persist sidebar_expanded: {err}");
     }
     Ok(())
@@ -283,9 +586,11 @@ fn persist_and_apply_service_zoom(
             Some(new_z)
         };
         let z = svc.zoom;
-        config::save_services(&state.app_data_dir, &services).unwrap_or_else(|err| {
-            eprintln!("[Taurium] Failed to save service zoom: {err}");
-        });
+        config::save_services(&webviews::current_data_dir(state), &services).unwrap_or_else(
+            |err| {
+                eprintln!("[Taurium] Failed to save service zoom: {err}");
+            },
+        );
         z
     };
     if let Some(wv) = app.get_webview(service_id) {
@@ -301,21 +606,50 @@ pub fn run() {
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_process::init())
         .setup(|app| {
-            let app_data_dir = app
-                .path()
-                .app_data_dir()
-                .expect("Failed to get app data dir");
+            let (data_root, source) = portable::resolve_data_dir(
+                std::env::args_os(),
+                portable::executable_dir().as_deref(),
+                app.path()
+                    .app_data_dir()
+                    .expect("Failed to get app data dir"),
+            );
+            if source != portable::DataDirSource::Default {
+                eprintln!(
+                    "[Taurium] Portable mode: data in {} ({source:?})",
+                    data_root.display()
+                );
+            }
+            let (profile_id, app_data_dir) = config::active_profile_dir(&data_root);
+            eprintln!("[Taurium] Using profile '{profile_id}'");
+            // A crash mid-save leaves its scratch file next to the config files.
+            config::remove_stale_temp_files(&data_root);
+            if app_data_dir != data_root {
+                config::remove_stale_temp_files(&app_data_dir);
+            }
 
             let (services, services_load_info) = match load_services(&app_data_dir) {
                 Ok(loaded) => {
                     if loaded.created_defaults {
                         eprintln!("[Taurium] Created default services.json");
                     }
+                    for name in &loaded.applied_migrations {
+                        eprintln!("[Taurium] Migrated services.json: {name}");
+                    }
+                    for entry in &loaded.skipped_entries {
+                        eprintln!(
+                            "[Taurium] Skipped services.json entry #{} ({}): {}",
+                            entry.index,
+                            entry.id.as_deref().unwrap_or("no id"),
+                            entry.error
+                        );
+                    }
                     (
                         loaded.services,
                         ServicesLoadInfo {
                             filtered_url_count: loaded.filtered_url_count,
+                            skipped_entries: loaded.skipped_entries,
                             load_error: None,
+                            applied_migrations: loaded.applied_migrations,
                         },
                     )
                 }
@@ -325,26 +659,46 @@ pub fn run() {
                         Vec::new(),
                         ServicesLoadInfo {
                             filtered_url_count: 0,
+                            skipped_entries: Vec::new(),
                             load_error: Some(err.to_string()),
+                            applied_migrations: Vec::new(),
                         },
                     )
                 }
             };
 
+            let preferences = load_preferences(&app_data_dir);
+
             // Register state FIRST
             let webview_state = WebviewState {
                 created_ids: std::sync::Mutex::new(Vec::new()),
                 active_id: std::sync::Mutex::new(None),
-                app_data_dir,
+                data_root,
+                app_data_dir: std::sync::Mutex::new(app_data_dir.clone()),
                 services: std::sync::Mutex::new(services.clone()),
                 navigated: std::sync::Mutex::new(HashSet::new()),
+                hibernated: std::sync::Mutex::new(HashSet::new()),
+                page_urls: std::sync::Mutex::new(HashMap::new()),
+                scroll_positions: std::sync::Mutex::new(HashMap::new()),
+                pending_scroll: std::sync::Mutex::new(HashMap::new()),
                 last_activity: std::sync::Mutex::new(HashMap::new()),
                 badge_counts: std::sync::Mutex::new(HashMap::new()),
                 sidebar_width: std::sync::Mutex::new(webviews::SIDEBAR_WIDTH),
                 services_load_info,
+                preferences: std::sync::Mutex::new(preferences),
+                notification_api_ids: std::sync::Mutex::new(HashSet::new()),
+                notification_throttle: std::sync::Mutex::new(Default::default()),
             };
             app.manage(webview_state);
             app.manage(ContextMenuTarget(std::sync::Mutex::new(None)));
+            app.manage(notifications::NotificationTracker::default());
+            app.manage(notifications::Snooze::default());
+
+            // Pick up hand edits of services.json / preferences.json. Not
+            // fatal: without it, changes still apply after a restart.
+            if let Err(err) = watcher::start(app.handle(), &app_data_dir) {
+                eprintln!("[Taurium] Failed to watch config files: {err}");
+            }
 
             // Create main window
             let window = tauri::window::WindowBuilder::new(app, "main")
@@ -399,11 +753,25 @@ pub fn run() {
 
             // Listen for window resize events
             let app_handle = app.handle().clone();
-            window.on_window_event(move |event| {
-                if let tauri::WindowEvent::Resized(_) = event {
+            window.on_window_event(move |event| match event {
+                tauri::WindowEvent::Resized(_) => {
                     let state = app_handle.state::<WebviewState>();
                     webviews::resize_all_webviews(&app_handle, &state);
                 }
+                // Clicking a notification brings the app to the front; Linux
+                // reports the click itself (see notifications.rs). Opt-in, as
+                // an alt-tab looks just the same.
+                #[cfg(not(target_os = "linux"))]
+                tauri::WindowEvent::Focused(true) => {
+                    let state = app_handle.state::<WebviewState>();
+                    if webviews::current_preferences(&state).notification_focus_switches {
+                        notifications::handle_activation(
+                            &app_handle,
+                            notifications::Activation::WindowFocused,
+                        );
+                    }
+                }
+                _ => {}
             });
 
             // Handle context menu events
@@ -472,6 +840,19 @@ pub fn run() {
                 webviews::check_hibernation(&app_handle, &state);
             });
 
+            // Memory pressure: check every 15 seconds (no-op without /proc)
+            let app_handle = app.handle().clone();
+            std::thread::spawn(move || loop {
+                std::thread::sleep(std::time::Duration::from_secs(15));
+                let state = app_handle.state::<WebviewState>();
+                webviews::check_memory_pressure(&app_handle, &state);
+            });
+
+            // Services marked "load at startup": loaded in the background,
+            // a few seconds apart
+            let app_handle = app.handle().clone();
+            std::thread::spawn(move || webviews::preload_services(&app_handle));
+
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -480,10 +861,22 @@ pub fn run() {
             switch_service,
             get_last_active_service,
             save_services_cmd,
+            generate_service_id,
+            rename_service_id,
             open_settings,
             restart_app,
             reload_service,
             get_badge_counts,
+            report_badge_count,
+            report_scroll_position,
+            forward_web_notification,
+            snooze_notifications,
+            unsnooze_notifications,
+            get_snooze_until,
+            get_notification_history,
+            clear_notification_history,
+            get_service_resource_usage,
+            focus_service,
             get_service_url,
             show_service_context_menu,
             get_preferences,
@@ -492,9 +885,42 @@ pub fn run() {
             set_sidebar_width,
             apply_services,
             get_services_load_info,
+            list_profiles,
+            create_profile,
+            switch_profile,
+            delete_profile,
+            list_services_snapshots,
+            diff_services_snapshot,
+            restore_services_snapshot,
+            export_workspace,
+            import_workspace,
             fetch_service_certificate,
             trust_service_certificate,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    /// The names listed between `start` and the next `end` in `source`.
+    fn listed_names(source: &str, start: &str, end: &str) -> Vec<String> {
+        let list = &source[source.find(start).expect("list start") + start.len()..];
+        let list = &list[..list.find(end).expect("list end")];
+        list.split(',')
+            .map(|name| name.trim().trim_matches('"').to_string())
+            .filter(|name| !name.is_empty())
+            .collect()
+    }
+
+    #[test]
+    fn build_script_declares_every_command() {
+        // A command missing from build.rs gets no permission and is refused
+        // at runtime, which no compiler error would catch.
+        let mut declared = listed_names(include_str!("../build.rs"), ".commands(&[", "]");
+        let mut registered = listed_names(include_str!("lib.rs"), "generate_handler![", "]");
+        declared.sort();
+        registered.sort();
+        assert_eq!(declared, registered);
+    }
+}
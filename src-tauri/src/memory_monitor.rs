@@ -0,0 +1,387 @@
+//! Memory pressure (Linux): system memory from `/proc/meminfo` and the RSS of
+//! Taurium's process tree (the app plus every WebKit web/network process it
+//! spawned). Above the `memory_limit_mb` preference, services are hibernated
+//! least recently used first (see `webviews::check_memory_pressure`).
+//! The same process tree, split per service, backs the resource usage report
+//! of the settings page. On WebKitGTK that split only reaches the processes
+//! that give their data directory away, i.e. each service's network process
+//! (cookies, cache, storage, service workers): web processes, which render
+//! the pages, carry no path of the service and are children of Taurium
+//! itself, so they are counted with the rest.
+//!
+//! The parsers are pure; only [`read_memory_usage`] and
+//! [`read_process_samples`] touch `/proc`.
+#![cfg_attr(not(target_os = "linux"), allow(dead_code))]
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Below this share of available system memory, the machine itself is under
+/// pressure.
+const MIN_AVAILABLE_PERCENT: u64 = 10;
+
+/// Share of system memory Taurium must hold for a low-memory system to
+/// count: hibernating services frees little when something else is the hog.
+const MIN_APP_SHARE_PERCENT: u64 = 10;
+
+/// Unit of the CPU times of `/proc/<pid>/stat` (USER_HZ), 100 on every
+/// architecture Linux exposes to user space.
+const CLOCK_TICKS_PER_SEC: u64 = 100;
+
+/// System-wide memory, in kB.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SystemMemory {
+    pub total_kb: u64,
+    pub available_kb: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryUsage {
+    pub system: SystemMemory,
+    /// Resident memory of Taurium and all its descendant processes, in kB.
+    pub app_rss_kb: u64,
+}
+
+/// Value in kB of a `Key:   1234 kB` line of `/proc/meminfo` or
+/// `/proc/<pid>/status`.
+fn kb_field(content: &str, key: &str) -> Option<u64> {
+    content.lines().find_map(|line| {
+        let rest = line.strip_prefix(key)?.strip_prefix(':')?;
+        rest.split_whitespace().next()?.parse().ok()
+    })
+}
+
+pub fn parse_meminfo(content: &str) -> Option<SystemMemory> {
+    Some(SystemMemory {
+        total_kb: kb_field(content, "MemTotal")?,
+        available_kb: kb_field(content, "MemAvailable")?,
+    })
+}
+
+/// Resident set size of one process from `/proc/<pid>/status` (absent for
+/// kernel threads and zombies).
+pub fn parse_vm_rss_kb(status: &str) -> Option<u64> {
+    kb_field(status, "VmRSS")
+}
+
+/// Parent pid from `/proc/<pid>/stat`. The command name (2nd field) is in
+/// parentheses and may itself contain spaces or parentheses, so fields are
+/// counted from the last `)`.
+pub fn parse_ppid(stat: &str) -> Option<u32> {
+    let after_comm = &stat[stat.rfind(')')? + 1..];
+    // state, then ppid
+    after_comm.split_whitespace().nth(1)?.parse().ok()
+}
+
+/// User + system CPU time (clock ticks) from `/proc/<pid>/stat`: fields 14
+/// and 15, i.e. the 12th and 13th after the command name.
+pub fn parse_cpu_ticks(stat: &str) -> Option<u64> {
+    let mut fields = stat[stat.rfind(')')? + 1..].split_whitespace().skip(11);
+    let utime: u64 = fields.next()?.parse().ok()?;
+    let stime: u64 = fields.next()?.parse().ok()?;
+    Some(utime + stime)
+}
+
+/// `root` and all its descendants, from `(pid, ppid)` pairs.
+pub fn process_tree(root: u32, processes: &[(u32, u32)]) -> Vec<u32> {
+    let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+    for &(pid, ppid) in processes {
+        children.entry(ppid).or_default().push(pid);
+    }
+    let mut tree = vec![root];
+    let mut next = 0;
+    while let Some(&pid) = tree.get(next) {
+        if let Some(kids) = children.get(&pid) {
+            tree.extend(kids.iter().filter(|k| **k != pid));
+        }
+        next += 1;
+    }
+    tree
+}
+
+/// One process of Taurium's tree.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProcessSample {
+    pub pid: u32,
+    pub ppid: u32,
+    pub rss_kb: u64,
+    pub cpu_ticks: u64,
+    /// Command line arguments and open files, to find the service the
+    /// process works for.
+    pub paths: Vec<PathBuf>,
+}
+
+/// Resources used by a group of processes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ProcessUsage {
+    pub processes: u32,
+    pub rss_kb: u64,
+    /// Total CPU time since the processes started.
+    pub cpu_time_ms: u64,
+}
+
+impl ProcessUsage {
+    fn add(&mut self, sample: &ProcessSample) {
+        self.processes += 1;
+        self.rss_kb += sample.rss_kb;
+        self.cpu_time_ms += sample.cpu_ticks * 1000 / CLOCK_TICKS_PER_SEC;
+    }
+}
+
+/// Split the samples between services. Each service has its own data
+/// directory: a process with it in its command line or open files works for
+/// that service, and so do its descendants. What is left (Taurium itself,
+/// processes no path gives away, such as WebKitGTK web processes) is
+/// returned apart.
+pub fn usage_by_data_dir(
+    samples: &[ProcessSample],
+    data_dirs: &[(String, PathBuf)],
+) -> (HashMap<String, ProcessUsage>, ProcessUsage) {
+    let service_of = |sample: &ProcessSample| {
+        data_dirs.iter().find_map(|(id, dir)| {
+            sample
+                .paths
+                .iter()
+                .any(|path| path.starts_with(dir))
+                .then_some(id.as_str())
+        })
+    };
+    let pairs: Vec<(u32, u32)> = samples.iter().map(|s| (s.pid, s.ppid)).collect();
+    // Direct matches first, so they win over an inherited owner.
+    let mut owners: HashMap<u32, &str> = samples
+        .iter()
+        .filter_map(|s| service_of(s).map(|id| (s.pid, id)))
+        .collect();
+    for (pid, id) in owners.clone() {
+        for descendant in process_tree(pid, &pairs) {
+            owners.entry(descendant).or_insert(id);
+        }
+    }
+
+    let mut per_service: HashMap<String, ProcessUsage> = HashMap::new();
+    let mut other = ProcessUsage::default();
+    for sample in samples {
+        match owners.get(&sample.pid) {
+            Some(id) => per_service.entry(id.to_string()).or_default().add(sample),
+            None => other.add(sample),
+        }
+    }
+    (per_service, other)
+}
+
+/// Whether services should be hibernated: Taurium uses more than
+/// `limit_mb`, or the system is running out of memory with Taurium among
+/// the big users. `0` disables it.
+pub fn is_under_pressure(usage: &MemoryUsage, limit_mb: u32) -> bool {
+    if limit_mb == 0 {
+        return false;
+    }
+    let over_limit = usage.app_rss_kb > u64::from(limit_mb) * 1024;
+    let system_low =
+        usage.system.available_kb * 100 < usage.system.total_kb * MIN_AVAILABLE_PERCENT;
+    let big_user = usage.app_rss_kb * 100 >= usage.system.total_kb * MIN_APP_SHARE_PERCENT;
+    over_limit || (system_low && big_user)
+}
+
+/// Current memory usage; `None` where `/proc` isn't available.
+#[cfg(target_os = "linux")]
+pub fn read_memory_usage() -> Option<MemoryUsage> {
+    let system = parse_meminfo(&std::fs::read_to_string("/proc/meminfo").ok()?)?;
+    // Processes may exit between the listing and the read: skip them.
+    let app_rss_kb = app_process_tree()?
+        .iter()
+        .filter_map(|pid| std::fs::read_to_string(format!("/proc/{pid}/status")).ok())
+        .filter_map(|status| parse_vm_rss_kb(&status))
+        .sum();
+    Some(MemoryUsage { system, app_rss_kb })
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read_memory_usage() -> Option<MemoryUsage> {
+    None
+}
+
+/// Taurium's pid and those of all its descendants.
+#[cfg(target_os = "linux")]
+fn app_process_tree() -> Option<Vec<u32>> {
+    let processes: Vec<(u32, u32)> = std::fs::read_dir("/proc")
+        .ok()?
+        .filter_map(|entry| {
+            let pid: u32 = entry.ok()?.file_name().to_str()?.parse().ok()?;
+            let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+            Some((pid, parse_ppid(&stat)?))
+        })
+        .collect();
+    Some(process_tree(std::process::id(), &processes))
+}
+
+/// Command line arguments (`--opt=value` gives `value`) and open files of a
+/// process. Other users' processes have unreadable fds: only the command
+/// line is left.
+#[cfg(target_os = "linux")]
+fn process_paths(pid: u32) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = std::fs::read(format!("/proc/{pid}/cmdline"))
+        .map(|cmdline| {
+            cmdline
+                .split(|b| *b == 0)
+                .filter_map(|arg| std::str::from_utf8(arg).ok())
+                .map(|arg| arg.split_once('=').map_or(arg, |(_, value)| value))
+                .filter(|arg| arg.starts_with('/'))
+                .map(PathBuf::from)
+                .collect()
+        })
+        .unwrap_or_default();
+    if let Ok(fds) = std::fs::read_dir(format!("/proc/{pid}/fd")) {
+        paths.extend(
+            fds.filter_map(|fd| std::fs::read_link(fd.ok()?.path()).ok())
+                .filter(|target| target.is_absolute()),
+        );
+    }
+    paths
+}
+
+/// A sample of every process of Taurium's tree; `None` where `/proc` isn't
+/// available. Taurium's own paths are left out: it opens files of every
+/// service.
+#[cfg(target_os = "linux")]
+pub fn read_process_samples() -> Option<Vec<ProcessSample>> {
+    let own_pid = std::process::id();
+    let samples = app_process_tree()?
+        .into_iter()
+        .filter_map(|pid| {
+            let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+            let status = std::fs::read_to_string(format!("/proc/{pid}/status")).ok()?;
+            Some(ProcessSample {
+                pid,
+                ppid: parse_ppid(&stat)?,
+                rss_kb: parse_vm_rss_kb(&status).unwrap_or(0),
+                cpu_ticks: parse_cpu_ticks(&stat).unwrap_or(0),
+                paths: if pid == own_pid {
+                    Vec::new()
+                } else {
+                    process_paths(pid)
+                },
+            })
+        })
+        .collect();
+    Some(samples)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read_process_samples() -> Option<Vec<ProcessSample>> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn proc_files_are_parsed() {
+        let meminfo = "MemTotal:       16318480 kB\nMemFree:         1022344 kB\nMemAvailable:    8123456 kB\n";
+        assert_eq!(
+            parse_meminfo(meminfo),
+            Some(SystemMemory {
+                total_kb: 16_318_480,
+                available_kb: 8_123_456,
+            })
+        );
+        // Very old kernels have no MemAvailable.
+        assert_eq!(parse_meminfo("MemTotal: 1024 kB\n"), None);
+
+        let status = "Name:\tWebKitWebProces\nVmPeak:\t  900000 kB\nVmRSS:\t  254312 kB\n";
+        assert_eq!(parse_vm_rss_kb(status), Some(254_312));
+        assert_eq!(parse_vm_rss_kb("Name:\tkworker/0:1\n"), None);
+
+        assert_eq!(
+            parse_ppid("4242 (WebKitWebProces) S 4100 4100 4100 0 -1"),
+            Some(4100)
+        );
+        // A command name with spaces and parentheses.
+        assert_eq!(parse_ppid("77 (a) b (c)) R 12 77 77 0"), Some(12));
+        assert_eq!(parse_ppid("garbage"), None);
+
+        let stat = "4242 (Web Content) S 4100 4100 4100 0 -1 4194560 1 0 0 0 350 42 0 0 20 0";
+        assert_eq!(parse_cpu_ticks(stat), Some(392));
+        assert_eq!(parse_cpu_ticks("4242 (short) S 1 2"), None);
+    }
+
+    #[test]
+    fn process_tree_collects_all_descendants() {
+        // 100 → bwrap 200 → web process 300; 100 → network 400; 500 unrelated.
+        let processes = [(200, 100), (300, 200), (400, 100), (500, 1), (100, 1)];
+        let mut tree = process_tree(100, &processes);
+        tree.sort_unstable();
+        assert_eq!(tree, vec![100, 200, 300, 400]);
+        assert_eq!(process_tree(999, &processes), vec![999]);
+    }
+
+    #[test]
+    fn usage_is_split_by_data_directory() {
+        let sample = |pid, ppid, rss_kb, paths: &[&str]| ProcessSample {
+            pid,
+            ppid,
+            rss_kb,
+            cpu_ticks: 50,
+            paths: paths.iter().map(PathBuf::from).collect(),
+        };
+        let data = Path::new("/data/webview_data");
+        let samples = [
+            sample(100, 1, 300, &[]),
+            // Network process holding the cookies of "mail".
+            sample(200, 100, 80, &["/data/webview_data/mail/Cookies"]),
+            // Sandbox launcher for "chat", and the web process inside.
+            sample(300, 100, 10, &["/usr/bin/bwrap", "/data/webview_data/chat"]),
+            sample(301, 300, 400, &[]),
+            // "mail2" must not match the "mail" directory.
+            sample(400, 100, 60, &["/data/webview_data/mail2/Cookies"]),
+            // Nothing tells which service this one works for.
+            sample(500, 100, 120, &["/usr/lib/webkit/WebKitWebProcess"]),
+        ];
+        let dirs = vec![
+            ("mail".to_string(), data.join("mail")),
+            ("chat".to_string(), data.join("chat")),
+            ("idle".to_string(), data.join("idle")),
+        ];
+        let (per_service, other) = usage_by_data_dir(&samples, &dirs);
+        assert_eq!(
+            per_service.get("mail"),
+            Some(&ProcessUsage {
+                processes: 1,
+                rss_kb: 80,
+                cpu_time_ms: 500,
+            })
+        );
+        assert_eq!(
+            per_service.get("chat"),
+            Some(&ProcessUsage {
+                processes: 2,
+                rss_kb: 410,
+                cpu_time_ms: 1000,
+            })
+        );
+        assert_eq!(per_service.get("idle"), None);
+        assert_eq!(other.processes, 3);
+        assert_eq!(other.rss_kb, 480);
+    }
+
+    #[test]
+    fn pressure_needs_a_limit() {
+        let usage = |app_mb: u64, available_percent: u64| MemoryUsage {
+            system: SystemMemory {
+                total_kb: 1000 * 1024,
+                available_kb: available_percent * 10 * 1024,
+            },
+            app_rss_kb: app_mb * 1024,
+        };
+        assert!(!is_under_pressure(&usage(5000, 1), 0));
+        assert!(!is_under_pressure(&usage(2000, 50), 2048));
+        assert!(is_under_pressure(&usage(2049, 50), 2048));
+        assert!(is_under_pressure(&usage(100, 9), 2048));
+        assert!(!is_under_pressure(&usage(100, 10), 2048));
+        // Low memory that Taurium has little to do with.
+        assert!(!is_under_pressure(&usage(99, 1), 2048));
+    }
+}
@@ -0,0 +1,107 @@
+//! Recent activity: a bounded log of the badge notifications, kept in
+//! notification_history.json (per profile) so it outlives the desktop toasts.
+
+use crate::config::{write_atomic, ConfigError};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Number of records kept; the oldest are dropped first.
+pub const NOTIFICATION_HISTORY_LIMIT: usize = 200;
+
+/// Serializes the read-modify-write of the log (merged notifications are
+/// recorded from timer threads).
+static HISTORY_LOCK: Mutex<()> = Mutex::new(());
+
+/// One emitted notification.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NotificationRecord {
+    pub service_id: String,
+    /// Unix time in milliseconds.
+    pub time: u64,
+    pub body: String,
+    /// Unread count of the service when the notification was emitted.
+    pub count: u32,
+}
+
+fn history_path(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("notification_history.json")
+}
+
+/// Records, newest first. A missing or unreadable log is an empty history.
+pub fn load_history(app_data_dir: &Path) -> Vec<NotificationRecord> {
+    fs::read_to_string(history_path(app_data_dir))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn append_record(app_data_dir: &Path, record: NotificationRecord) -> Result<(), ConfigError> {
+    append_record_with_limit(app_data_dir, record, NOTIFICATION_HISTORY_LIMIT)
+}
+
+fn append_record_with_limit(
+    app_data_dir: &Path,
+    record: NotificationRecord,
+    limit: usize,
+) -> Result<(), ConfigError> {
+    let _guard = HISTORY_LOCK.lock().unwrap_or_else(|p| p.into_inner());
+    let mut records = load_history(app_data_dir);
+    records.insert(0, record);
+    records.truncate(limit);
+    fs::create_dir_all(app_data_dir)?;
+    let json = serde_json::to_string(&records)?;
+    write_atomic(&history_path(app_data_dir), json.as_bytes())?;
+    Ok(())
+}
+
+pub fn clear_history(app_data_dir: &Path) -> Result<(), ConfigError> {
+    let _guard = HISTORY_LOCK.lock().unwrap_or_else(|p| p.into_inner());
+    match fs::remove_file(history_path(app_data_dir)) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn record(service_id: &str, time: u64) -> NotificationRecord {
+        NotificationRecord {
+            service_id: service_id.to_string(),
+            time,
+            body: format!("New notification from {service_id}"),
+            count: 1,
+        }
+    }
+
+    #[test]
+    fn history_is_bounded_newest_first_and_clearable() {
+        let dir = tempdir().expect("tempdir should be created");
+        assert!(load_history(dir.path()).is_empty());
+        // Clearing an empty history is fine.
+        clear_history(dir.path()).unwrap();
+
+        for time in 1..=4 {
+            append_record_with_limit(dir.path(), record("slack", time), 3).unwrap();
+        }
+        let times: Vec<u64> = load_history(dir.path()).iter().map(|r| r.time).collect();
+        assert_eq!(times, vec![4, 3, 2]);
+
+        append_record(dir.path(), record("mail", 5)).unwrap();
+        assert_eq!(load_history(dir.path())[0], record("mail", 5));
+
+        clear_history(dir.path()).unwrap();
+        assert!(load_history(dir.path()).is_empty());
+
+        // A corrupted log doesn't block new records.
+        fs::write(history_path(dir.path()), "not json").unwrap();
+        assert!(load_history(dir.path()).is_empty());
+        append_record(dir.path(), record("mail", 6)).unwrap();
+        assert_eq!(load_history(dir.path()).len(), 1);
+    }
+}
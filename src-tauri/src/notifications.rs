@@ -0,0 +1,754 @@
+//! Desktop notifications of the services.
+//!
+//! - Web Notification API bridge: service pages get a `window.Notification`
+//!   (and page-side `ServiceWorkerRegistration.showNotification`) that
+//!   forwards to native notifications, so the user sees who wrote and what
+//!   instead of the badge-delta summary ("3 new notifications from Slack").
+//!   Notifications a service worker shows by itself (push messages handled
+//!   in its own global scope) never go through the shim, which only runs in
+//!   pages: for those the badge delta stays the only signal.
+//! - Click-to-focus: every notification is recorded with its service, and
+//!   activating it raises the window on that service. On Linux the click is
+//!   reported by the notification server (default action); elsewhere the
+//!   notification plugin has no click callback, so, when the user opts in
+//!   (`notification_focus_switches`), the main window gaining focus shortly
+//!   after a notification counts as activating it.
+//! - Do Not Disturb: nothing is shown during quiet hours (see
+//!   `quiet_hours.rs`) or while notifications are snoozed.
+
+use base64::Engine;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+#[cfg(target_os = "linux")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{AppHandle, Manager};
+#[cfg(not(target_os = "linux"))]
+use tauri_plugin_notification::NotificationExt;
+
+use crate::config::{self, NOTIFY_ALL};
+use crate::quiet_hours;
+use crate::webviews::{self, WebviewState};
+
+/// How long after a notification a focus of the main window is taken as a
+/// click on it (platforms without a click callback).
+const FOCUS_ACTIVATION_WINDOW: Duration = Duration::from_secs(10);
+
+/// Notifications remembered for routing; older ones can't be clicked anyway.
+const MAX_TRACKED_NOTIFICATIONS: usize = 50;
+
+/// Longest body shown in a native notification (chat previews can be whole
+/// messages).
+const MAX_BODY_CHARS: usize = 300;
+
+/// Largest icon accepted from a page, decoded.
+const MAX_ICON_BYTES: usize = 256 * 1024;
+
+/// Icon files kept on disk; the oldest go first beyond that.
+const MAX_CACHED_ICONS: usize = 100;
+
+/// Notifications waiting for a click at the same time (Linux: one blocked
+/// thread each, until the notification is closed). Beyond that, new ones
+/// are shown without a click action.
+#[cfg(target_os = "linux")]
+const MAX_CLICK_LISTENERS: usize = 16;
+
+#[cfg(target_os = "linux")]
+static CLICK_LISTENERS: AtomicUsize = AtomicUsize::new(0);
+
+/// Initialization script installed in every service webview. Icons are
+/// fetched by the page itself (its cookies, its origin) and sent as data
+/// URLs: native notifications can't load remote images.
+pub const NOTIFICATION_SHIM: &str = r#"(function () {
+  if (window.__tauriumNotificationShim) return;
+  window.__tauriumNotificationShim = true;
+  var MAX_ICON_BYTES = 262144;
+
+  function iconData(icon) {
+    if (!icon) return Promise.resolve(null);
+    var url;
+    try { url = new URL(String(icon), location.href).href; } catch (e) { return Promise.resolve(null); }
+    if (url.indexOf("data:") === 0) return Promise.resolve(url);
+    return fetch(url, { credentials: "include" })
+      .then(function (r) { return r.ok ? r.blob() : null; })
+      .then(function (blob) {
+        if (!blob || blob.size > MAX_ICON_BYTES) return null;
+        return new Promise(function (resolve) {
+          var reader = new FileReader();
+          reader.onload = function () { resolve(reader.result); };
+          reader.onerror = function () { resolve(null); };
+          reader.readAsDataURL(blob);
+        });
+      })
+      .catch(function () { return null; });
+  }
+
+  function forward(title, options) {
+    var ipc = window.__TAURI_INTERNALS__;
+    if (!ipc) return;
+    options = options || {};
+    iconData(options.icon).then(function (icon) {
+      ipc.invoke("forward_web_notification", {
+        notification: {
+          title: String(title == null ? "" : title),
+          body: options.body == null ? null : String(options.body),
+          icon: icon
+        }
+      }).catch(function () {});
+    });
+  }
+
+  class TauriumNotification extends EventTarget {
+    constructor(title, options) {
+      super();
+      options = options || {};
+      this.title = String(title == null ? "" : title);
+      this.body = options.body == null ? "" : String(options.body);
+      this.icon = options.icon || "";
+      this.tag = options.tag || "";
+      this.data = options.data === undefined ? null : options.data;
+      this.onclick = null;
+      this.onshow = null;
+      this.onclose = null;
+      this.onerror = null;
+      forward(title, options);
+    }
+    close() {}
+    static get permission() { return "granted"; }
+    static requestPermission(callback) {
+      if (typeof callback === "function") callback("granted");
+      return Promise.resolve("granted");
+    }
+  }
+  Object.defineProperty(window, "Notification", {
+    value: TauriumNotification, writable: true, configurable: true
+  });
+
+  if (window.ServiceWorkerRegistration) {
+    ServiceWorkerRegistration.prototype.showNotification = function (title, options) {
+      forward(title, options);
+      return Promise.resolve();
+    };
+    ServiceWorkerRegistration.prototype.getNotifications = function () {
+      return Promise.resolve([]);
+    };
+  }
+  if (navigator.permissions && navigator.permissions.query) {
+    var query = navigator.permissions.query.bind(navigator.permissions);
+    navigator.permissions.query = function (desc) {
+      if (desc && desc.name === "notifications") {
+        return Promise.resolve({ state: "granted", status: "granted", onchange: null });
+      }
+      return query(desc);
+    };
+  }
+})();"#;
+
+/// What a page passed to `new Notification(title, options)`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct WebNotification {
+    pub title: String,
+    #[serde(default)]
+    pub body: Option<String>,
+    /// `data:` URL built by the shim, if the page gave a fetchable icon.
+    #[serde(default)]
+    pub icon: Option<String>,
+}
+
+/// Native notification title and body. The page title is usually the sender
+/// ("Alice"), so the service name goes first to say where it comes from.
+pub(crate) fn native_notification_text(
+    service_name: &str,
+    notification: &WebNotification,
+) -> (String, String) {
+    let page_title = notification.title.trim();
+    let title = if page_title.is_empty() || page_title == service_name {
+        service_name.to_string()
+    } else {
+        format!("{service_name} · {page_title}")
+    };
+    let body = notification.body.as_deref().unwrap_or("").trim();
+    let body = if body.chars().count() > MAX_BODY_CHARS {
+        let cut: String = body.chars().take(MAX_BODY_CHARS - 1).collect();
+        format!("{}…", cut.trim_end())
+    } else {
+        body.to_string()
+    };
+    (title, body)
+}
+
+/// Decode an image `data:` URL into `(extension, bytes)`.
+fn decode_icon_data_url(url: &str) -> Option<(&'static str, Vec<u8>)> {
+    let rest = url.strip_prefix("data:")?;
+    let (meta, data) = rest.split_once(',')?;
+    let mime = meta.strip_suffix(";base64")?;
+    let ext = match mime {
+        "image/png" => "png",
+        "image/jpeg" => "jpg",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        "image/x-icon" | "image/vnd.microsoft.icon" => "ico",
+        _ => return None,
+    };
+    // Rough pre-check so a huge string isn't decoded just to be rejected.
+    if data.len() / 4 * 3 > MAX_ICON_BYTES {
+        return None;
+    }
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(data)
+        .ok()?;
+    (!bytes.is_empty() && bytes.len() <= MAX_ICON_BYTES).then_some((ext, bytes))
+}
+
+/// Write the icon to `dir` under a content-addressed name (the same avatar
+/// is sent over and over) and return its path.
+fn cache_icon(dir: &Path, data_url: &str) -> Option<PathBuf> {
+    let (ext, bytes) = decode_icon_data_url(data_url)?;
+    let hash: String = Sha256::digest(&bytes)
+        .iter()
+        .take(16)
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    let path = dir.join(format!("{hash}.{ext}"));
+    if !path.exists() {
+        fs::create_dir_all(dir).ok()?;
+        prune_icon_cache(dir, MAX_CACHED_ICONS - 1);
+        fs::write(&path, bytes).ok()?;
+    }
+    Some(path)
+}
+
+/// Remove the least recently written icons in `dir` beyond `keep`.
+fn prune_icon_cache(dir: &Path, keep: usize) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut icons: Vec<(SystemTime, PathBuf)> = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            Some((entry.metadata().ok()?.modified().ok()?, entry.path()))
+        })
+        .collect();
+    if icons.len() <= keep {
+        return;
+    }
+    icons.sort();
+    for (_, path) in &icons[..icons.len() - keep] {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// How the user reached a notification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Activation {
+    /// The notification with this id was clicked.
+    Clicked(u64),
+    /// The main window gained focus (only used where clicks aren't reported,
+    /// and only when the user opted in).
+    #[cfg_attr(target_os = "linux", allow(dead_code))]
+    WindowFocused,
+}
+
+#[derive(Debug)]
+struct SentNotification {
+    id: u64,
+    service_id: String,
+    sent_at: Instant,
+    /// A focus event can only stand for a click on a notification that was
+    /// shown while the window was in the background.
+    focus_activates: bool,
+}
+
+/// Which service each recent notification came from. Pure bookkeeping, the
+/// clock is passed in.
+#[derive(Debug, Default)]
+pub struct NotificationRouter {
+    next_id: u64,
+    sent: VecDeque<SentNotification>,
+}
+
+impl NotificationRouter {
+    /// Remember a notification of `service_id`; returns its id.
+    pub fn record(&mut self, service_id: &str, now: Instant, window_focused: bool) -> u64 {
+        self.next_id += 1;
+        if self.sent.len() == MAX_TRACKED_NOTIFICATIONS {
+            self.sent.pop_front();
+        }
+        self.sent.push_back(SentNotification {
+            id: self.next_id,
+            service_id: service_id.to_string(),
+            sent_at: now,
+            focus_activates: !window_focused,
+        });
+        self.next_id
+    }
+
+    /// Service to show for `activation`, if any. A focus event picks the
+    /// latest recent background notification and is used up: the next focus
+    /// (e.g. the one caused by raising the window) routes nowhere.
+    pub fn route(&mut self, activation: Activation, now: Instant) -> Option<String> {
+        match activation {
+            Activation::Clicked(id) => {
+                let index = self.sent.iter().position(|n| n.id == id)?;
+                self.sent.remove(index).map(|n| n.service_id)
+            }
+            Activation::WindowFocused => {
+                let target = self
+                    .sent
+                    .iter()
+                    .rev()
+                    .find(|n| {
+                        n.focus_activates
+                            && now.duration_since(n.sent_at) <= FOCUS_ACTIVATION_WINDOW
+                    })
+                    .map(|n| n.service_id.clone());
+                for n in &mut self.sent {
+                    n.focus_activates = false;
+                }
+                target
+            }
+        }
+    }
+
+    /// Drop the notifications of a removed service.
+    pub fn forget_service(&mut self, service_id: &str) {
+        self.sent.retain(|n| n.service_id != service_id);
+    }
+
+    /// Follow a service id rename: its notifications still route to it.
+    pub fn rename_service(&mut self, old_id: &str, new_id: &str) {
+        for n in self.sent.iter_mut().filter(|n| n.service_id == old_id) {
+            n.service_id = new_id.to_string();
+        }
+    }
+
+    /// Drop every notification (profile switch: their services are gone).
+    /// Ids keep counting, so a late click on an old one routes nowhere.
+    pub fn forget_all(&mut self) {
+        self.sent.clear();
+    }
+}
+
+/// Managed state holding the [`NotificationRouter`].
+#[derive(Default)]
+pub struct NotificationTracker(pub Mutex<NotificationRouter>);
+
+/// Managed state: end of the current "snooze" (temporary Do Not Disturb).
+/// In memory only, a restart ends it.
+#[derive(Default)]
+pub struct Snooze(pub Mutex<Option<SystemTime>>);
+
+/// End of the snooze, if one is running.
+pub fn snoozed_until(app: &AppHandle) -> Option<SystemTime> {
+    let until = match app.state::<Snooze>().0.lock() {
+        Ok(guard) => *guard,
+        Err(poisoned) => *poisoned.into_inner(),
+    };
+    until.filter(|until| *until > SystemTime::now())
+}
+
+pub fn snooze(app: &AppHandle, duration: Duration) -> SystemTime {
+    let until = SystemTime::now() + duration;
+    match app.state::<Snooze>().0.lock() {
+        Ok(mut guard) => *guard = Some(until),
+        Err(poisoned) => *poisoned.into_inner() = Some(until),
+    }
+    eprintln!(
+        "[Taurium] Notifications snoozed for {} min",
+        duration.as_secs() / 60
+    );
+    until
+}
+
+pub fn unsnooze(app: &AppHandle) {
+    match app.state::<Snooze>().0.lock() {
+        Ok(mut guard) => *guard = None,
+        Err(poisoned) => *poisoned.into_inner() = None,
+    }
+}
+
+/// Local weekday (0 = Monday) and minute of the day, as taken by the pure
+/// schedule functions (`quiet_hours`, `notify_schedule`).
+pub fn local_time() -> (u32, u32) {
+    use chrono::{Datelike, Timelike};
+    let now = chrono::Local::now();
+    (
+        now.weekday().num_days_from_monday(),
+        now.hour() * 60 + now.minute(),
+    )
+}
+
+/// Whether Do Not Disturb holds notifications back right now.
+fn do_not_disturb(app: &AppHandle) -> bool {
+    if snoozed_until(app).is_some() {
+        return true;
+    }
+    let prefs = webviews::current_preferences(&app.state::<WebviewState>());
+    let (weekday, minute) = local_time();
+    quiet_hours::is_quiet_time(&prefs.quiet_hours, weekday, minute)
+}
+
+/// Show a desktop notification on behalf of `service_id` and flash the
+/// taskbar. Every service notification goes through here so a click on it
+/// can be routed back (see [`handle_activation`]), and so Do Not Disturb
+/// applies to all of them. Returns whether the notification was shown.
+pub fn show_service_notification(
+    app: &AppHandle,
+    service_id: &str,
+    title: &str,
+    body: &str,
+    icon: Option<&str>,
+) -> bool {
+    if do_not_disturb(app) {
+        eprintln!("[Taurium] Notification from '{service_id}' held back (do not disturb)");
+        return false;
+    }
+    let window_focused = app
+        .get_window("main")
+        .and_then(|w| w.is_focused().ok())
+        .unwrap_or(false);
+    let id = match app.state::<NotificationTracker>().0.lock() {
+        Ok(mut router) => router.record(service_id, Instant::now(), window_focused),
+        Err(e) => {
+            eprintln!("[Taurium] Mutex poisoned: {}", e);
+            return false;
+        }
+    };
+    let shown = match show_native(app, id, title, body, icon) {
+        Ok(()) => {
+            eprintln!("[Taurium] Notification sent successfully");
+            true
+        }
+        Err(e) => {
+            eprintln!("[Taurium] Notification error: {}", e);
+            false
+        }
+    };
+    // Draw attention by briefly flashing the taskbar entry.
+    webviews::flash_taskbar(app);
+    shown
+}
+
+/// Linux: talk to the notification server directly so the click comes back
+/// (the plugin drops the handle).
+#[cfg(target_os = "linux")]
+fn show_native(
+    app: &AppHandle,
+    id: u64,
+    title: &str,
+    body: &str,
+    icon: Option<&str>,
+) -> Result<(), String> {
+    let mut notification = notify_rust::Notification::new();
+    notification.summary(title).body(body);
+    match icon {
+        Some(icon) => notification.icon(icon),
+        None => notification.auto_icon(),
+    };
+    let slot = ListenerSlot::take(&CLICK_LISTENERS, MAX_CLICK_LISTENERS);
+    if slot.is_some() {
+        notification.action("default", "Ouvrir");
+    } else {
+        eprintln!("[Taurium] Too many notifications waiting for a click, showing one without");
+    }
+    let handle = notification.show().map_err(|e| e.to_string())?;
+    let Some(slot) = slot else {
+        return Ok(());
+    };
+    let app = app.clone();
+    // Blocks until the notification is clicked or closed.
+    std::thread::spawn(move || {
+        handle.wait_for_action(|action| {
+            if action == "default" {
+                handle_activation(&app, Activation::Clicked(id));
+            }
+        });
+        drop(slot);
+    });
+    Ok(())
+}
+
+/// One of at most `max` concurrent users of `counter`, given back on drop.
+#[cfg(target_os = "linux")]
+struct ListenerSlot(&'static AtomicUsize);
+
+#[cfg(target_os = "linux")]
+impl ListenerSlot {
+    fn take(counter: &'static AtomicUsize, max: usize) -> Option<Self> {
+        counter
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+                (n < max).then_some(n + 1)
+            })
+            .ok()
+            .map(|_| ListenerSlot(counter))
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for ListenerSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn show_native(
+    app: &AppHandle,
+    _id: u64,
+    title: &str,
+    body: &str,
+    icon: Option<&str>,
+) -> Result<(), String> {
+    let mut builder = app.notification().builder().title(title).body(body);
+    if let Some(icon) = icon {
+        builder = builder.icon(icon);
+    }
+    builder.show().map_err(|e| e.to_string())
+}
+
+/// Route a notification activation: raise the main window and switch to the
+/// service that produced the notification.
+pub fn handle_activation(app: &AppHandle, activation: Activation) {
+    let service_id = match app.state::<NotificationTracker>().0.lock() {
+        Ok(mut router) => router.route(activation, Instant::now()),
+        Err(e) => {
+            eprintln!("[Taurium] Mutex poisoned: {}", e);
+            return;
+        }
+    };
+    let Some(service_id) = service_id else {
+        return;
+    };
+    eprintln!("[Taurium] Notification activated, focusing '{service_id}'");
+    let app = app.clone();
+    // switch_to may create the webview, which must not run on the main thread
+    // (where window events are delivered).
+    std::thread::spawn(move || focus_service(&app, &service_id));
+}
+
+/// Bring the main window forward on `service_id`, as a click on one of its
+/// notifications does.
+pub fn focus_service(app: &AppHandle, service_id: &str) {
+    if let Some(window) = app.get_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+    let state = app.state::<WebviewState>();
+    if let Err(e) = webviews::switch_to(app, &state, service_id) {
+        eprintln!("[Taurium] Failed to focus '{service_id}': {e}");
+        return;
+    }
+    let data_dir = webviews::current_data_dir(&state);
+    if let Err(e) = config::update_state(&data_dir, |app_state| {
+        app_state.last_active_service = Some(service_id.to_string());
+    }) {
+        eprintln!("[Taurium] Failed to save state: {e}");
+    }
+    if let Some(sidebar) = app.get_webview("sidebar") {
+        let id_json = serde_json::to_string(service_id).unwrap_or_default();
+        sidebar
+            .eval(format!(
+                "window.__setActiveService && window.__setActiveService({id_json})"
+            ))
+            .ok();
+    }
+}
+
+/// Show a notification forwarded by the shim of `service_id`'s webview.
+pub fn handle_web_notification(app: &AppHandle, service_id: &str, notification: WebNotification) {
+    let state = app.state::<WebviewState>();
+    let (weekday, minute) = local_time();
+    let service = match state.services.lock() {
+        Ok(services) => services
+            .iter()
+            .find(|s| s.id == service_id)
+            .map(|s| (s.name.clone(), s.notify_level_at(weekday, minute))),
+        Err(e) => {
+            eprintln!("[Taurium] Mutex poisoned: {}", e);
+            return;
+        }
+    };
+    // Popups share the shim through their opener's session; ignore them.
+    let Some((service_name, level)) = service else {
+        return;
+    };
+
+    // From now on this page speaks for itself: badge deltas stop notifying.
+    if let Ok(mut ids) = state.notification_api_ids.lock() {
+        ids.insert(service_id.to_string());
+    }
+
+    if !webviews::current_preferences(&state).notifications_enabled || level != NOTIFY_ALL {
+        return;
+    }
+    let (title, body) = native_notification_text(&service_name, &notification);
+    let icon_dir = std::env::temp_dir().join("taurium-notification-icons");
+    let icon = notification
+        .icon
+        .as_deref()
+        .and_then(|url| cache_icon(&icon_dir, url))
+        .map(|path| path.to_string_lossy().into_owned());
+    // A page firing notifications in a loop gets the badge rate limit too.
+    let held = webviews::HeldNotification {
+        title: title.clone(),
+        body: body.clone(),
+        icon: icon.clone(),
+    };
+    if !webviews::throttle_web_notification(app, service_id, held) {
+        eprintln!("[Taurium] Web notification from '{service_id}' held back (rate limit)");
+        return;
+    }
+
+    eprintln!("[Taurium] Web notification from '{service_id}': {title}");
+    show_service_notification(app, service_id, &title, &body, icon.as_deref());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notification_text_names_the_service_and_trims_the_body() {
+        let notification = WebNotification {
+            title: " Alice ".to_string(),
+            body: Some("x".repeat(400)),
+            icon: None,
+        };
+        let (title, body) = native_notification_text("Slack", &notification);
+        assert_eq!(title, "Slack · Alice");
+        assert_eq!(body.chars().count(), MAX_BODY_CHARS);
+        assert!(body.ends_with('…'));
+
+        let (title, body) = native_notification_text("Slack", &WebNotification::default());
+        assert_eq!((title.as_str(), body.as_str()), ("Slack", ""));
+    }
+
+    #[test]
+    fn clicks_route_to_the_notifying_service_once() {
+        let mut router = NotificationRouter::default();
+        let now = Instant::now();
+        let mail = router.record("mail", now, false);
+        let chat = router.record("chat", now, false);
+        assert_ne!(mail, chat);
+
+        assert_eq!(
+            router.route(Activation::Clicked(mail), now),
+            Some("mail".to_string())
+        );
+        assert_eq!(router.route(Activation::Clicked(mail), now), None);
+        assert_eq!(router.route(Activation::Clicked(999), now), None);
+
+        router.forget_service("chat");
+        assert_eq!(router.route(Activation::Clicked(chat), now), None);
+
+        // A renamed service keeps its notifications.
+        let mail = router.record("mail", now, false);
+        router.rename_service("mail", "email");
+        assert_eq!(
+            router.route(Activation::Clicked(mail), now),
+            Some("email".to_string())
+        );
+    }
+
+    #[test]
+    fn focus_routes_to_the_latest_background_notification() {
+        let mut router = NotificationRouter::default();
+        let start = Instant::now();
+        router.record("mail", start, false);
+        router.record("chat", start + Duration::from_secs(1), false);
+        // Shown while the user was looking at the window: focus can't mean it.
+        router.record("news", start + Duration::from_secs(2), true);
+
+        let soon = start + Duration::from_secs(3);
+        assert_eq!(
+            router.route(Activation::WindowFocused, soon),
+            Some("chat".to_string())
+        );
+        // Used up, e.g. by the focus event of raising the window.
+        assert_eq!(router.route(Activation::WindowFocused, soon), None);
+
+        router.record("mail", soon, false);
+        let late = soon + FOCUS_ACTIVATION_WINDOW + Duration::from_secs(1);
+        assert_eq!(router.route(Activation::WindowFocused, late), None);
+    }
+
+    #[test]
+    fn forgotten_notifications_route_nowhere() {
+        let mut router = NotificationRouter::default();
+        let now = Instant::now();
+        let old = router.record("mail", now, false);
+        router.forget_all();
+        assert_eq!(router.route(Activation::WindowFocused, now), None);
+
+        let new = router.record("mail", now, false);
+        assert_ne!(old, new);
+        assert_eq!(router.route(Activation::Clicked(old), now), None);
+        assert_eq!(
+            router.route(Activation::Clicked(new), now),
+            Some("mail".to_string())
+        );
+    }
+
+    #[test]
+    fn router_keeps_a_bounded_history() {
+        let mut router = NotificationRouter::default();
+        let now = Instant::now();
+        let first = router.record("svc", now, false);
+        for _ in 0..MAX_TRACKED_NOTIFICATIONS {
+            router.record("svc", now, false);
+        }
+        assert_eq!(router.sent.len(), MAX_TRACKED_NOTIFICATIONS);
+        assert_eq!(router.route(Activation::Clicked(first), now), None);
+    }
+
+    #[test]
+    fn only_small_image_data_urls_become_icon_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let png = "data:image/png;base64,iVBORw0KGgo=";
+        let path = cache_icon(dir.path(), png).expect("PNG data URL should be cached");
+        assert_eq!(path.extension().and_then(|e| e.to_str()), Some("png"));
+        assert_eq!(cache_icon(dir.path(), png), Some(path));
+
+        assert_eq!(cache_icon(dir.path(), "https://example.com/a.png"), None);
+        assert_eq!(cache_icon(dir.path(), "data:text/html;base64,PGI+"), None);
+        let huge = format!("data:image/png;base64,{}", "A".repeat(MAX_ICON_BYTES * 2));
+        assert_eq!(cache_icon(dir.path(), &huge), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn click_listeners_are_bounded() {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let first = ListenerSlot::take(&COUNTER, 2).unwrap();
+        let second = ListenerSlot::take(&COUNTER, 2).unwrap();
+        assert!(ListenerSlot::take(&COUNTER, 2).is_none());
+        drop(first);
+        let third = ListenerSlot::take(&COUNTER, 2);
+        assert!(third.is_some());
+        drop((second, third));
+        assert_eq!(COUNTER.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn icon_cache_drops_the_oldest_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        for (i, name) in ["old.png", "mid.png", "new.png"].iter().enumerate() {
+            let file = fs::File::create(dir.path().join(name)).unwrap();
+            file.set_modified(base + Duration::from_secs(i as u64))
+                .unwrap();
+        }
+        prune_icon_cache(dir.path(), 2);
+        assert!(!dir.path().join("old.png").exists());
+        assert!(dir.path().join("mid.png").exists());
+        assert!(dir.path().join("new.png").exists());
+        // Within the bound nothing goes.
+        prune_icon_cache(dir.path(), 2);
+        assert!(dir.path().join("mid.png").exists());
+    }
+}
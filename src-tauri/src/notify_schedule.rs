@@ -0,0 +1,187 @@
+//! Per-service notification schedules: time windows in which a service uses
+//! another level than its `notify` one (e.g. Slack notifies during office
+//! hours and only shows a badge the rest of the time).
+//!
+//! Pure, like `quiet_hours.rs`: callers pass the local weekday and time.
+
+use crate::config::{NOTIFY_ALL, NOTIFY_BADGE, NOTIFY_OFF};
+use crate::quiet_hours::parse_time;
+use serde::{Deserialize, Serialize};
+
+const DAYS_PER_WEEK: u32 = 7;
+
+/// One window of a service schedule, stored in services.json.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ScheduleRule {
+    /// Days the window starts on, from Monday (0) to Sunday (6). Empty means
+    /// every day.
+    #[serde(default)]
+    pub days: Vec<u32>,
+    /// Local start time, `"HH:MM"`. A start later than `end` spans midnight:
+    /// the part after midnight still belongs to the start day (a Friday
+    /// 22:00 → 02:00 window covers Saturday 01:00). Equal times mean the
+    /// whole day.
+    pub start: String,
+    pub end: String,
+    /// Level in effect inside the window: `"all"`, `"badge"` or `"off"`.
+    pub level: String,
+}
+
+fn is_level(value: &str) -> bool {
+    matches!(value, NOTIFY_ALL | NOTIFY_BADGE | NOTIFY_OFF)
+}
+
+fn has_day(days: &[u32], weekday: u32) -> bool {
+    days.is_empty() || days.contains(&weekday)
+}
+
+impl ScheduleRule {
+    /// Whether the rule is usable: readable times, known level, real days.
+    pub fn is_valid(&self) -> bool {
+        parse_time(&self.start).is_some()
+            && parse_time(&self.end).is_some()
+            && is_level(&self.level)
+            && self.days.iter().all(|d| *d < DAYS_PER_WEEK)
+    }
+
+    /// Whether `minute_of_day` on `weekday` (0 = Monday) falls in the window.
+    pub fn covers(&self, weekday: u32, minute_of_day: u32) -> bool {
+        let (Some(start), Some(end)) = (parse_time(&self.start), parse_time(&self.end)) else {
+            return false;
+        };
+        let weekday = weekday % DAYS_PER_WEEK;
+        let minute = minute_of_day % (24 * 60);
+        if start == end {
+            return has_day(&self.days, weekday);
+        }
+        if start < end {
+            return has_day(&self.days, weekday) && start <= minute && minute < end;
+        }
+        let yesterday = (weekday + DAYS_PER_WEEK - 1) % DAYS_PER_WEEK;
+        (has_day(&self.days, weekday) && minute >= start)
+            || (has_day(&self.days, yesterday) && minute < end)
+    }
+}
+
+/// Level of the first rule covering the given time, if any. The caller falls
+/// back to the service's own level (see `Service::notify_level_at`).
+pub fn scheduled_level(rules: &[ScheduleRule], weekday: u32, minute_of_day: u32) -> Option<&str> {
+    rules
+        .iter()
+        .find(|rule| rule.is_valid() && rule.covers(weekday, minute_of_day))
+        .map(|rule| rule.level.as_str())
+}
+
+/// Drop unusable rules (hand-edited services.json) and tidy the day lists.
+pub fn normalize(rules: Vec<ScheduleRule>) -> Vec<ScheduleRule> {
+    rules
+        .into_iter()
+        .filter(ScheduleRule::is_valid)
+        .map(|mut rule| {
+            rule.days.sort_unstable();
+            rule.days.dedup();
+            if rule.days.len() == DAYS_PER_WEEK as usize {
+                rule.days.clear();
+            }
+            rule
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MON: u32 = 0;
+    const FRI: u32 = 4;
+    const SAT: u32 = 5;
+    const SUN: u32 = 6;
+
+    fn at(h: u32, m: u32) -> u32 {
+        h * 60 + m
+    }
+
+    fn rule(days: &[u32], start: &str, end: &str, level: &str) -> ScheduleRule {
+        ScheduleRule {
+            days: days.to_vec(),
+            start: start.to_string(),
+            end: end.to_string(),
+            level: level.to_string(),
+        }
+    }
+
+    #[test]
+    fn office_hours_window() {
+        let rules = [rule(&[0, 1, 2, 3, 4], "09:00", "18:00", NOTIFY_ALL)];
+        assert_eq!(scheduled_level(&rules, MON, at(9, 0)), Some(NOTIFY_ALL));
+        assert_eq!(scheduled_level(&rules, FRI, at(17, 59)), Some(NOTIFY_ALL));
+        assert_eq!(scheduled_level(&rules, MON, at(8, 59)), None);
+        assert_eq!(scheduled_level(&rules, MON, at(18, 0)), None);
+        assert_eq!(scheduled_level(&rules, SAT, at(12, 0)), None);
+    }
+
+    #[test]
+    fn overnight_window_belongs_to_its_start_day() {
+        let rules = [rule(&[FRI], "22:00", "02:00", NOTIFY_OFF)];
+        assert_eq!(scheduled_level(&rules, FRI, at(21, 59)), None);
+        assert_eq!(scheduled_level(&rules, FRI, at(23, 0)), Some(NOTIFY_OFF));
+        assert_eq!(scheduled_level(&rules, SAT, at(1, 59)), Some(NOTIFY_OFF));
+        assert_eq!(scheduled_level(&rules, SAT, at(2, 0)), None);
+        // Friday early morning is Thursday's window, which doesn't exist.
+        assert_eq!(scheduled_level(&rules, FRI, at(1, 0)), None);
+
+        // Sunday night wraps to Monday morning.
+        let rules = [rule(&[SUN], "20:00", "07:00", NOTIFY_BADGE)];
+        assert_eq!(scheduled_level(&rules, MON, at(6, 0)), Some(NOTIFY_BADGE));
+        assert_eq!(scheduled_level(&rules, SUN, at(6, 0)), None);
+    }
+
+    #[test]
+    fn empty_days_and_equal_times() {
+        let every_day = [rule(&[], "12:00", "13:00", NOTIFY_BADGE)];
+        for day in 0..7 {
+            assert_eq!(
+                scheduled_level(&every_day, day, at(12, 30)),
+                Some(NOTIFY_BADGE)
+            );
+        }
+        let weekend = [rule(&[SAT, SUN], "00:00", "00:00", NOTIFY_OFF)];
+        assert_eq!(scheduled_level(&weekend, SAT, at(0, 0)), Some(NOTIFY_OFF));
+        assert_eq!(scheduled_level(&weekend, SUN, at(23, 59)), Some(NOTIFY_OFF));
+        assert_eq!(scheduled_level(&weekend, MON, at(0, 0)), None);
+    }
+
+    #[test]
+    fn first_matching_rule_wins_and_invalid_rules_are_skipped() {
+        let rules = [
+            rule(&[], "25:00", "26:00", NOTIFY_OFF),
+            rule(&[], "09:00", "18:00", "loud"),
+            rule(&[9], "09:00", "18:00", NOTIFY_OFF),
+            rule(&[MON], "12:00", "14:00", NOTIFY_OFF),
+            rule(&[], "09:00", "18:00", NOTIFY_ALL),
+        ];
+        assert_eq!(scheduled_level(&rules, MON, at(12, 30)), Some(NOTIFY_OFF));
+        assert_eq!(scheduled_level(&rules, MON, at(10, 0)), Some(NOTIFY_ALL));
+        assert_eq!(scheduled_level(&rules, SAT, at(12, 30)), Some(NOTIFY_ALL));
+        assert_eq!(scheduled_level(&rules, SAT, at(20, 0)), None);
+        assert_eq!(scheduled_level(&[], MON, at(12, 0)), None);
+    }
+
+    #[test]
+    fn normalize_drops_bad_rules_and_tidies_days() {
+        let rules = normalize(vec![
+            rule(&[4, 0, 4], "09:00", "18:00", NOTIFY_ALL),
+            rule(&[0, 1, 2, 3, 4, 5, 6], "12:00", "13:00", NOTIFY_BADGE),
+            rule(&[], "9h", "18:00", NOTIFY_ALL),
+            rule(&[7], "09:00", "18:00", NOTIFY_ALL),
+            rule(&[], "09:00", "18:00", ""),
+        ]);
+        assert_eq!(
+            rules,
+            vec![
+                rule(&[0, 4], "09:00", "18:00", NOTIFY_ALL),
+                rule(&[], "12:00", "13:00", NOTIFY_BADGE),
+            ]
+        );
+    }
+}
@@ -0,0 +1,195 @@
+//! Where Taurium keeps its data: the platform app data dir by default, or a
+//! user-chosen directory for portable installs (USB sticks, kiosks).
+//!
+//! Resolution order:
+//! 1. `--data-dir <path>` (or `--data-dir=<path>`) on the command line;
+//! 2. a `taurium.portable` marker file next to the executable — its first
+//!    non-empty line names the directory, an empty marker means `data/`;
+//! 3. the platform app data dir.
+//!
+//! Relative paths are resolved against the executable's directory, so a
+//! portable install keeps working whatever drive letter or mount point the
+//! stick gets.
+
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const DATA_DIR_ARG: &str = "--data-dir";
+pub const PORTABLE_MARKER: &str = "taurium.portable";
+/// Directory used when the marker file is empty.
+const DEFAULT_PORTABLE_DIR: &str = "data";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataDirSource {
+    Argument,
+    Marker,
+    Default,
+}
+
+/// `value` without surrounding whitespace. Left as is when it isn't valid
+/// Unicode: paths may be arbitrary bytes on Unix.
+fn trim_os(value: &OsStr) -> &OsStr {
+    value.to_str().map_or(value, |v| OsStr::new(v.trim()))
+}
+
+/// The part of `arg` after `--data-dir=`, if it starts with it.
+fn strip_data_dir_prefix(arg: &OsStr) -> Option<&OsStr> {
+    let prefix = format!("{DATA_DIR_ARG}=");
+    let rest = arg.as_encoded_bytes().strip_prefix(prefix.as_bytes())?;
+    // SAFETY: `rest` comes from an `OsStr` and is split right after a
+    // non-empty UTF-8 prefix, which `from_encoded_bytes_unchecked` allows.
+    Some(unsafe { OsStr::from_encoded_bytes_unchecked(rest) })
+}
+
+/// Value of `--data-dir`, if present. The last occurrence wins. Works on raw
+/// arguments: a non-Unicode path must not stop Taurium from starting.
+fn data_dir_arg<I, S>(args: I) -> Option<OsString>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let mut found = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let arg = arg.as_ref();
+        if arg == DATA_DIR_ARG {
+            if let Some(value) = args.next() {
+                found = Some(value.as_ref().to_os_string());
+            }
+        } else if let Some(value) = strip_data_dir_prefix(arg) {
+            found = Some(value.to_os_string());
+        }
+    }
+    found.filter(|v| !trim_os(v).is_empty())
+}
+
+/// Directory named by the marker file in `base_dir`, if the marker exists.
+fn marker_dir(base_dir: &Path) -> Option<PathBuf> {
+    let content = fs::read_to_string(base_dir.join(PORTABLE_MARKER)).ok()?;
+    let dir = content
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or(DEFAULT_PORTABLE_DIR);
+    Some(base_dir.join(dir))
+}
+
+/// Pure resolution policy (see the module docs). `base_dir` is the directory
+/// holding the executable, when known.
+pub fn resolve_data_dir<I, S>(
+    args: I,
+    base_dir: Option<&Path>,
+    default_dir: PathBuf,
+) -> (PathBuf, DataDirSource)
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    if let Some(dir) = data_dir_arg(args) {
+        let dir = PathBuf::from(trim_os(&dir));
+        let dir = match base_dir {
+            Some(base) if dir.is_relative() => base.join(dir),
+            _ => dir,
+        };
+        return (dir, DataDirSource::Argument);
+    }
+    if let Some(dir) = base_dir.and_then(marker_dir) {
+        return (dir, DataDirSource::Marker);
+    }
+    (default_dir, DataDirSource::Default)
+}
+
+/// Directory the portable install lives in. For an AppImage the executable
+/// runs from a temporary mount, so use the location of the image itself.
+pub fn executable_dir() -> Option<PathBuf> {
+    if let Some(appimage) = std::env::var_os("APPIMAGE") {
+        return Path::new(&appimage).parent().map(Path::to_path_buf);
+    }
+    std::env::current_exe()
+        .ok()?
+        .parent()
+        .map(Path::to_path_buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn data_dir_argument_takes_precedence() {
+        let exe = tempdir().unwrap();
+        fs::write(exe.path().join(PORTABLE_MARKER), "").unwrap();
+        let default = PathBuf::from("/home/user/.local/share/taurium");
+
+        let (dir, source) = resolve_data_dir(
+            ["taurium", "--data-dir", "profile"],
+            Some(exe.path()),
+            default.clone(),
+        );
+        assert_eq!(
+            (dir, source),
+            (exe.path().join("profile"), DataDirSource::Argument)
+        );
+
+        let absolute = exe.path().join("elsewhere");
+        let arg = format!("--data-dir={}", absolute.display());
+        let (dir, _) = resolve_data_dir(["taurium", arg.as_str()], Some(exe.path()), default);
+        assert_eq!(dir, absolute);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_unicode_arguments_are_accepted() {
+        use std::os::unix::ffi::OsStringExt;
+
+        let exe = tempdir().unwrap();
+        let default = PathBuf::from("/home/user/.local/share/taurium");
+        let path = OsString::from_vec(b"/media/cl\xe9/taurium".to_vec());
+
+        let mut joined = OsString::from("--data-dir=");
+        joined.push(&path);
+        let args = [
+            OsString::from("taurium"),
+            OsString::from_vec(b"\xff".to_vec()),
+            joined,
+        ];
+        assert_eq!(
+            resolve_data_dir(args, Some(exe.path()), default.clone()),
+            (PathBuf::from(&path), DataDirSource::Argument)
+        );
+
+        let args = [
+            OsString::from("taurium"),
+            OsString::from(DATA_DIR_ARG),
+            path.clone(),
+        ];
+        assert_eq!(
+            resolve_data_dir(args, Some(exe.path()), default).0,
+            PathBuf::from(&path)
+        );
+    }
+
+    #[test]
+    fn marker_file_selects_portable_dir() {
+        let exe = tempdir().unwrap();
+        let default = PathBuf::from("/home/user/.local/share/taurium");
+        assert_eq!(
+            resolve_data_dir(["taurium"], Some(exe.path()), default.clone()),
+            (default.clone(), DataDirSource::Default)
+        );
+
+        fs::write(exe.path().join(PORTABLE_MARKER), "\n").unwrap();
+        assert_eq!(
+            resolve_data_dir(["taurium"], Some(exe.path()), default.clone()),
+            (exe.path().join("data"), DataDirSource::Marker)
+        );
+
+        fs::write(exe.path().join(PORTABLE_MARKER), "\n  kiosk-data \n").unwrap();
+        assert_eq!(
+            resolve_data_dir(["taurium", "--data-dir"], Some(exe.path()), default),
+            (exe.path().join("kiosk-data"), DataDirSource::Marker)
+        );
+    }
+}
@@ -0,0 +1,147 @@
+//! Do Not Disturb: recurring quiet hours. While they are active, desktop
+//! notifications are held back but badges keep updating. The temporary
+//! "snooze" lives in memory (see `notifications::Snooze`).
+//!
+//! Everything here is pure: callers pass the local weekday and time.
+
+use serde::{Deserialize, Serialize};
+
+const MINUTES_PER_DAY: u32 = 24 * 60;
+
+/// Days are numbered from Monday (0) to Sunday (6).
+const SATURDAY: u32 = 5;
+const SUNDAY: u32 = 6;
+
+/// Quiet hours schedule, stored in preferences.json.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct QuietHours {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Local start time, `"HH:MM"`. A start later than `end` spans midnight
+    /// (e.g. 19:00 → 08:00); equal times mean no daily window.
+    #[serde(default = "default_start")]
+    pub start: String,
+    #[serde(default = "default_end")]
+    pub end: String,
+    /// Quiet all day on Saturday and Sunday.
+    #[serde(default)]
+    pub weekends: bool,
+}
+
+fn default_start() -> String {
+    "19:00".to_string()
+}
+
+fn default_end() -> String {
+    "08:00".to_string()
+}
+
+impl Default for QuietHours {
+    fn default() -> Self {
+        QuietHours {
+            enabled: false,
+            start: default_start(),
+            end: default_end(),
+            weekends: false,
+        }
+    }
+}
+
+/// Minutes since midnight for `"HH:MM"`.
+pub fn parse_time(value: &str) -> Option<u32> {
+    let (h, m) = value.trim().split_once(':')?;
+    let (h, m) = (h.parse::<u32>().ok()?, m.parse::<u32>().ok()?);
+    (h < 24 && m < 60).then_some(h * 60 + m)
+}
+
+/// Replace unreadable times with the defaults (hand-edited preferences.json).
+pub fn normalize(mut quiet: QuietHours) -> QuietHours {
+    if parse_time(&quiet.start).is_none() {
+        quiet.start = default_start();
+    }
+    if parse_time(&quiet.end).is_none() {
+        quiet.end = default_end();
+    }
+    quiet
+}
+
+/// Whether `minute_of_day` on `weekday` (0 = Monday) falls in the quiet
+/// hours.
+pub fn is_quiet_time(quiet: &QuietHours, weekday: u32, minute_of_day: u32) -> bool {
+    if !quiet.enabled {
+        return false;
+    }
+    if quiet.weekends && (weekday == SATURDAY || weekday == SUNDAY) {
+        return true;
+    }
+    let (Some(start), Some(end)) = (parse_time(&quiet.start), parse_time(&quiet.end)) else {
+        return false;
+    };
+    let minute = minute_of_day % MINUTES_PER_DAY;
+    if start <= end {
+        start <= minute && minute < end
+    } else {
+        minute >= start || minute < end
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(h: u32, m: u32) -> u32 {
+        h * 60 + m
+    }
+
+    #[test]
+    fn overnight_window_and_weekends() {
+        let quiet = QuietHours {
+            enabled: true,
+            weekends: true,
+            ..QuietHours::default()
+        };
+        // Wednesday
+        assert!(!is_quiet_time(&quiet, 2, at(18, 59)));
+        assert!(is_quiet_time(&quiet, 2, at(19, 0)));
+        assert!(is_quiet_time(&quiet, 2, at(3, 0)));
+        assert!(!is_quiet_time(&quiet, 2, at(8, 0)));
+        // Saturday and Sunday, all day
+        assert!(is_quiet_time(&quiet, 5, at(12, 0)));
+        assert!(is_quiet_time(&quiet, 6, at(12, 0)));
+
+        let disabled = QuietHours {
+            enabled: false,
+            ..quiet
+        };
+        assert!(!is_quiet_time(&disabled, 6, at(3, 0)));
+    }
+
+    #[test]
+    fn same_day_window_and_bad_times() {
+        let lunch = QuietHours {
+            enabled: true,
+            start: "12:00".to_string(),
+            end: "13:30".to_string(),
+            weekends: false,
+        };
+        assert!(!is_quiet_time(&lunch, 0, at(11, 59)));
+        assert!(is_quiet_time(&lunch, 0, at(13, 29)));
+        assert!(!is_quiet_time(&lunch, 5, at(14, 0)));
+
+        let empty = QuietHours {
+            end: "12:00".to_string(),
+            ..lunch.clone()
+        };
+        assert!(!is_quiet_time(&empty, 0, at(12, 0)));
+
+        assert_eq!(parse_time("7:05"), Some(at(7, 5)));
+        assert_eq!(parse_time("24:00"), None);
+        assert_eq!(parse_time("-1:00"), None);
+        let broken = normalize(QuietHours {
+            start: "soon".to_string(),
+            ..lunch
+        });
+        assert_eq!(broken.start, "19:00");
+        assert_eq!(broken.end, "13:30");
+    }
+}
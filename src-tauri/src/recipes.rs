@@ -1,7 +1,35 @@
 use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
 
 const RECIPES_JSON: &str = include_str!("../recipes.json");
 
+/// Regex used by the selector strategy when the recipe doesn't give one.
+const DEFAULT_BADGE_REGEX: &str = r"(\d+)";
+
+/// How often the injected badge script re-reads the page.
+const BADGE_POLL_MS: u32 = 2000;
+
+/// Stratégie de lecture du compteur de non-lus dans le DOM, pour les services
+/// qui ne l'affichent pas dans le titre (`(3) Boîte de réception`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BadgeStrategy {
+    /// Direct count: sum of the numbers found in the text of every element
+    /// matching `selector`; `regex` (JS syntax, first capture group if any)
+    /// defaults to the first integer. Indirect count: number of elements
+    /// matching `indirect_selector` (unread channels are usually a bare dot).
+    Selector {
+        selector: String,
+        #[serde(default)]
+        regex: Option<String>,
+        #[serde(default)]
+        indirect_selector: Option<String>,
+    },
+    /// Function body returning the direct count, or `{ direct, indirect }`,
+    /// for pages where a selector isn't enough.
+    Script { script: String },
+}
+
 /// Prédéfinition de service embarquée (catalogue « recipes »).
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Recipe {
@@ -11,6 +39,8 @@ pub struct Recipe {
     pub icon: String,
     #[serde(default)]
     pub user_agent: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub badge: Option<BadgeStrategy>,
 }
 
 /// Charge le catalogue embarqué, en filtrant les entrées invalides.
@@ -28,11 +58,108 @@ pub fn load_recipes() -> Vec<Recipe> {
             if r.user_agent.as_deref() == Some("") {
                 r.user_agent = None;
             }
+            let empty_badge = match &r.badge {
+                Some(BadgeStrategy::Selector { selector, .. }) => selector.trim().is_empty(),
+                Some(BadgeStrategy::Script { script }) => script.trim().is_empty(),
+                None => false,
+            };
+            if empty_badge {
+                r.badge = None;
+            }
             r
         })
         .collect()
 }
 
+/// Catalogue chargé une seule fois (consulté à chaque création de webview).
+fn catalog() -> &'static [Recipe] {
+    static CATALOG: OnceLock<Vec<Recipe>> = OnceLock::new();
+    CATALOG.get_or_init(load_recipes)
+}
+
+/// Badge strategy of the recipe whose URL has the same host as `service_url`.
+/// Services don't record which recipe they came from, and matching on the
+/// host also covers services added by hand.
+pub fn badge_strategy_for_url(service_url: &str) -> Option<&'static BadgeStrategy> {
+    find_badge_strategy(catalog(), service_url)
+}
+
+fn find_badge_strategy<'a>(recipes: &'a [Recipe], service_url: &str) -> Option<&'a BadgeStrategy> {
+    let host = url::Url::parse(service_url).ok()?.host_str()?.to_string();
+    recipes
+        .iter()
+        .filter(|r| r.badge.is_some())
+        .find(|r| {
+            url::Url::parse(&r.url)
+                .ok()
+                .and_then(|u| u.host_str().map(|h| h == host))
+                .unwrap_or(false)
+        })
+        .and_then(|r| r.badge.as_ref())
+}
+
+/// Initialization script polling the page with `strategy` and reporting
+/// changes through the `report_badge_count` command.
+pub fn badge_script(strategy: &BadgeStrategy) -> Option<String> {
+    let count_body = match strategy {
+        BadgeStrategy::Selector {
+            selector,
+            regex,
+            indirect_selector,
+        } => {
+            let selector_json = serde_json::to_string(selector).ok()?;
+            let regex_json =
+                serde_json::to_string(regex.as_deref().unwrap_or(DEFAULT_BADGE_REGEX)).ok()?;
+            let indirect = match indirect_selector
+                .as_deref()
+                .filter(|s| !s.trim().is_empty())
+            {
+                Some(sel) => format!(
+                    "document.querySelectorAll({}).length",
+                    serde_json::to_string(sel).ok()?
+                ),
+                None => "0".to_string(),
+            };
+            format!(
+                r#"var re = new RegExp({regex_json});
+  var total = 0;
+  document.querySelectorAll({selector_json}).forEach(function (el) {{
+    var m = re.exec(el.textContent || "");
+    if (m) total += parseInt(m[1] !== undefined ? m[1] : m[0], 10) || 0;
+  }});
+  return {{ direct: total, indirect: {indirect} }};"#
+            )
+        }
+        BadgeStrategy::Script { script } => format!("{script}\n"),
+    };
+    Some(format!(
+        r#"(function () {{
+  if (window.__tauriumBadgeTimer) return;
+  var last = "";
+  function count() {{
+  {count_body}
+  }}
+  function clean(n) {{
+    return Math.max(0, Math.floor(Number(n) || 0));
+  }}
+  function report() {{
+    var ipc = window.__TAURI_INTERNALS__;
+    if (!ipc || !document.body) return;
+    var c;
+    try {{ c = count(); }} catch (e) {{ return; }}
+    var args = c !== null && typeof c === "object"
+      ? {{ direct: clean(c.direct), indirect: clean(c.indirect) }}
+      : {{ direct: clean(c), indirect: 0 }};
+    var key = args.direct + "/" + args.indirect;
+    if (key === last) return;
+    last = key;
+    ipc.invoke("report_badge_count", args).catch(function () {{}});
+  }}
+  window.__tauriumBadgeTimer = setInterval(report, {BADGE_POLL_MS});
+}})();"#
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -53,4 +180,49 @@ mod tests {
         assert!(telegram.is_some());
         assert_eq!(telegram.unwrap().name, "Telegram");
     }
+
+    #[test]
+    fn badge_strategy_is_found_by_host_and_scripted() {
+        let recipes: Vec<Recipe> = serde_json::from_str(
+            r#"[
+              {"id": "chat", "name": "Chat", "url": "https://chat.example.com", "icon": "x",
+               "badge": {"type": "selector", "selector": ".unread", "regex": "(\\d+) new",
+                         "indirect_selector": ".channel.unread"}},
+              {"id": "mail", "name": "Mail", "url": "https://mail.example.com", "icon": "x"}
+            ]"#,
+        )
+        .unwrap();
+        let strategy = find_badge_strategy(&recipes, "https://chat.example.com/#/room/1")
+            .expect("same host should match");
+        assert_eq!(
+            find_badge_strategy(&recipes, "https://mail.example.com"),
+            None
+        );
+        assert_eq!(find_badge_strategy(&recipes, "not a url"), None);
+
+        let script = badge_script(strategy).unwrap();
+        assert!(script.contains(r#"new RegExp("(\\d+) new")"#));
+        assert!(script.contains(r#"querySelectorAll(".unread")"#));
+        assert!(script.contains(r#"indirect: document.querySelectorAll(".channel.unread").length"#));
+        assert!(script.contains("report_badge_count"));
+
+        let script = badge_script(&BadgeStrategy::Script {
+            script: "return 4;".to_string(),
+        })
+        .unwrap();
+        assert!(script.contains("return 4;"));
+    }
+
+    #[test]
+    fn embedded_badge_strategies_are_usable() {
+        let element = load_recipes()
+            .into_iter()
+            .find(|r| r.id == "element")
+            .unwrap();
+        assert!(matches!(
+            element.badge,
+            Some(BadgeStrategy::Selector { .. })
+        ));
+        assert!(badge_strategy_for_url("https://app.element.io/#/home").is_some());
+    }
 }
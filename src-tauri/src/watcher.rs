@@ -0,0 +1,271 @@
+//! Live reload of services.json / preferences.json when they are edited
+//! outside the app (PLAN.md allows hand-editing them in the app data dir).
+
+use crate::config::{self, ParsedServices, ServicesLoadInfo};
+use crate::webviews::{self, WebviewState};
+use notify_debouncer_mini::notify::{RecommendedWatcher, RecursiveMode};
+use notify_debouncer_mini::{new_debouncer, DebounceEventResult, Debouncer};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+/// Editors often save in several steps (truncate + write, or temp + rename);
+/// wait for the burst to settle before re-reading.
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Keeps the watcher alive for the lifetime of the app (dropping it stops
+/// the notifications).
+pub struct ConfigWatcher {
+    debouncer: Mutex<Debouncer<RecommendedWatcher>>,
+}
+
+impl ConfigWatcher {
+    /// Follow a profile switch: stop watching `old`, watch `new`.
+    pub fn rewatch(
+        &self,
+        old: &Path,
+        new: &Path,
+    ) -> Result<(), notify_debouncer_mini::notify::Error> {
+        let mut debouncer = match self.debouncer.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        // The old directory may be gone already (deleted profile).
+        let _ = debouncer.watcher().unwatch(old);
+        debouncer.watcher().watch(new, RecursiveMode::NonRecursive)
+    }
+}
+
+/// Which config files a batch of debounced events touched.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct ChangedConfig {
+    services: bool,
+    preferences: bool,
+}
+
+/// Match on the exact file name: our own atomic writes go through
+/// `<file>.<pid>.<n>.tmp` (only the final rename matters), and the history, backups
+/// and webview_data directory are not live config.
+fn changed_config<'a>(paths: impl IntoIterator<Item = &'a PathBuf>) -> ChangedConfig {
+    let mut changed = ChangedConfig::default();
+    for path in paths {
+        match path.file_name().and_then(|n| n.to_str()) {
+            Some("services.json") => changed.services = true,
+            Some("preferences.json") => changed.preferences = true,
+            _ => {}
+        }
+    }
+    changed
+}
+
+/// Start watching `app_data_dir` (non-recursively) and register the watcher
+/// as managed state. Must run after `WebviewState` is managed.
+pub fn start(
+    app: &AppHandle,
+    app_data_dir: &Path,
+) -> Result<(), notify_debouncer_mini::notify::Error> {
+    let handle = app.clone();
+    let mut debouncer = new_debouncer(DEBOUNCE, move |result: DebounceEventResult| {
+        let events = match result {
+            Ok(events) => events,
+            Err(err) => {
+                eprintln!("[Taurium] Config watcher error: {err}");
+                return;
+            }
+        };
+        let changed = changed_config(events.iter().map(|e| &e.path));
+        if changed.services {
+            reload_services(&handle);
+        }
+        if changed.preferences {
+            reload_preferences(&handle);
+        }
+    })?;
+    debouncer
+        .watcher()
+        .watch(app_data_dir, RecursiveMode::NonRecursive)?;
+    app.manage(ConfigWatcher {
+        debouncer: Mutex::new(debouncer),
+    });
+    eprintln!(
+        "[Taurium] Watching {} for config changes",
+        app_data_dir.display()
+    );
+    Ok(())
+}
+
+fn reload_services(app: &AppHandle) {
+    let state = app.state::<WebviewState>();
+    let data_dir = webviews::current_data_dir(&state);
+    let path = config::get_services_path(&data_dir);
+    // Deleted or mid-rename: wait for the next event rather than recreating
+    // the defaults under the user's feet.
+    let Ok(content) = fs::read_to_string(&path) else {
+        return;
+    };
+
+    // Check the document first: load_services moves an unparsable file to
+    // .bak, which must not happen to a file someone is still typing into.
+    match config::parse_services_document(&content) {
+        Ok(ParsedServices::Current { .. }) => {}
+        Ok(ParsedServices::TooNew(found)) => {
+            show_config_error(
+                app,
+                "services.json",
+                &config::LoadServicesError::UnsupportedSchemaVersion { found }.to_string(),
+            );
+            return;
+        }
+        Err(err) => {
+            eprintln!("[Taurium] Ignoring unparsable services.json edit: {err}");
+            show_config_error(app, "services.json", &err.to_string());
+            return;
+        }
+    }
+
+    // From the content checked above: the file may have changed again since.
+    let loaded = match config::load_services_from_str(&data_dir, &content) {
+        Ok(loaded) => loaded,
+        Err(err) => {
+            eprintln!("[Taurium] Failed to reload services: {err}");
+            show_config_error(app, "services.json", &err.to_string());
+            return;
+        }
+    };
+
+    // Our own saves (settings page) land here too; skip when nothing changed.
+    let unchanged = state
+        .services
+        .lock()
+        .map(|current| {
+            serde_json::to_value(&*current).ok() == serde_json::to_value(&loaded.services).ok()
+        })
+        .unwrap_or(false);
+    if !unchanged {
+        eprintln!("[Taurium] services.json changed on disk, applying");
+        if let Err(err) = webviews::apply_service_changes(app, &state, loaded.services) {
+            eprintln!("[Taurium] Failed to apply reloaded services: {err}");
+            return;
+        }
+        // The settings page keeps its own copy of the list; refresh it so a
+        // later save there doesn't overwrite the external edit.
+        if let Some(settings) = app.get_webview("settings") {
+            settings
+                .eval("window.__reloadServices && window.__reloadServices()")
+                .ok();
+        }
+    }
+
+    let info = ServicesLoadInfo {
+        filtered_url_count: loaded.filtered_url_count,
+        skipped_entries: loaded.skipped_entries,
+        load_error: None,
+        applied_migrations: loaded.applied_migrations,
+    };
+    if info != ServicesLoadInfo::default() {
+        if let (Some(sidebar), Ok(info_json)) =
+            (app.get_webview("sidebar"), serde_json::to_string(&info))
+        {
+            sidebar
+                .eval(format!(
+                    "window.__showServicesLoadInfo && window.__showServicesLoadInfo({info_json})"
+                ))
+                .ok();
+        }
+    }
+}
+
+fn reload_preferences(app: &AppHandle) {
+    let state = app.state::<WebviewState>();
+    let Ok(content) = fs::read_to_string(config::get_preferences_path(
+        &webviews::current_data_dir(&state),
+    )) else {
+        return;
+    };
+    let prefs = match config::parse_preferences(&content) {
+        Ok(prefs) => prefs,
+        Err(err) => {
+            eprintln!("[Taurium] Ignoring unparsable preferences.json edit: {err}");
+            show_config_error(app, "preferences.json", &err.to_string());
+            return;
+        }
+    };
+    let Ok(prefs_json) = serde_json::to_string(&prefs) else {
+        return;
+    };
+    // Our own saves already updated the cache and the sidebar.
+    if serde_json::to_string(&webviews::current_preferences(&state)).ok()
+        == Some(prefs_json.clone())
+    {
+        return;
+    }
+    if let Err(err) = webviews::set_preferences(&state, prefs) {
+        eprintln!("[Taurium] Failed to cache reloaded preferences: {err}");
+        return;
+    }
+    eprintln!("[Taurium] preferences.json changed on disk, applying");
+    if let Some(sidebar) = app.get_webview("sidebar") {
+        sidebar
+            .eval(format!(
+                "window.__applyPreferences && window.__applyPreferences({prefs_json})"
+            ))
+            .ok();
+    }
+}
+
+/// Surface a reload failure in the sidebar; the previous config stays active.
+fn show_config_error(app: &AppHandle, file: &str, error: &str) {
+    let Some(sidebar) = app.get_webview("sidebar") else {
+        return;
+    };
+    let (Ok(file_json), Ok(error_json)) =
+        (serde_json::to_string(file), serde_json::to_string(error))
+    else {
+        return;
+    };
+    sidebar
+        .eval(format!(
+            "window.__showConfigError && window.__showConfigError({file_json}, {error_json})"
+        ))
+        .ok();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_live_config_files_trigger_a_reload() {
+        let dir = PathBuf::from("/data/taurium");
+        let paths = [
+            dir.join("services.json.4242.0.tmp"),
+            dir.join("services.json.v1.bak"),
+            dir.join("state.json"),
+            dir.join("webview_data"),
+        ];
+        assert_eq!(changed_config(&paths), ChangedConfig::default());
+
+        let paths = [
+            dir.join("services.json.4242.0.tmp"),
+            dir.join("services.json"),
+        ];
+        assert_eq!(
+            changed_config(&paths),
+            ChangedConfig {
+                services: true,
+                preferences: false,
+            }
+        );
+
+        let paths = [dir.join("preferences.json"), dir.join("services.json")];
+        assert_eq!(
+            changed_config(&paths),
+            ChangedConfig {
+                services: true,
+                preferences: true,
+            }
+        );
+    }
+}
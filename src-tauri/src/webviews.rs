@@ -1,17 +1,20 @@
+use serde::Serialize;
 use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Mutex;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tauri::webview::{NewWindowFeatures, NewWindowResponse, PageLoadEvent};
 use tauri::{AppHandle, LogicalPosition, LogicalSize, Manager, Url, WebviewUrl};
-use tauri_plugin_notification::NotificationExt;
 
 use crate::config::{
-    extract_badge_count, load_preferences, Service, ServicesLoadInfo, NOTIFY_ALL, NOTIFY_OFF,
+    self, extract_badge_count, HibernationPolicy, Preferences, Service, ServicesLoadInfo,
+    MAX_BADGE_COUNT, NOTIFY_ALL, NOTIFY_OFF,
 };
 use crate::error::TauriumError;
+use crate::notification_history::{self, NotificationRecord};
+use crate::{memory_monitor, notifications, recipes};
 
 /// Minimum sidebar width / fallback (icons only). The actual width is driven by
 /// the frontend (it depends on icon size and the expanded state).
@@ -58,18 +61,147 @@ pub(crate) fn notification_body_for_badge_change(
                 .replace("{service}", service_name)
         }
     } else {
-        let new_msgs = count - prev_count;
-        if new_msgs == 1 {
-            NOTIFY_NEW_SINGLE.replace("{service}", service_name)
-        } else {
-            NOTIFY_NEW_MULTIPLE
-                .replace("{count}", &new_msgs.to_string())
-                .replace("{service}", service_name)
-        }
+        new_notifications_body(service_name, count - prev_count)
     };
     Some(body)
 }
 
+/// "New notification" / "N new notifications" body, also used for the
+/// merged notification of a throttled service.
+fn new_notifications_body(service_name: &str, new_msgs: u32) -> String {
+    if new_msgs == 1 {
+        NOTIFY_NEW_SINGLE.replace("{service}", service_name)
+    } else {
+        NOTIFY_NEW_MULTIPLE
+            .replace("{count}", &new_msgs.to_string())
+            .replace("{service}", service_name)
+    }
+}
+
+/// What to do with the new messages of one badge increase (see
+/// [`NotificationThrottle::record`]).
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum ThrottleDecision {
+    /// Notify now for this many new messages (held-back ones included).
+    Show(u32),
+    /// Too soon after the last notification: hold the messages back and call
+    /// [`NotificationThrottle::flush`] after this delay.
+    Defer(Duration),
+    /// Added to messages already waiting for a scheduled flush.
+    Merged,
+}
+
+/// A web notification held back by the throttle, shown as is when the
+/// deferral ends.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct HeldNotification {
+    pub title: String,
+    pub body: String,
+    pub icon: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct ThrottleEntry {
+    last_shown: Option<Instant>,
+    pending: u32,
+    flush_scheduled: bool,
+    /// Latest web notification among the pending messages.
+    held: Option<HeldNotification>,
+}
+
+/// Per-service rate limit for service notifications, from badge increases
+/// and from the web Notification API alike. After a notification, the
+/// service stays quiet for the minimum interval; increases arriving in that
+/// window are merged into a single notification shown when it ends: the
+/// latest held-back web notification if there is one, otherwise "N new
+/// notifications". Pure: the caller passes the clock and runs the timer.
+#[derive(Debug, Default)]
+pub struct NotificationThrottle {
+    entries: HashMap<String, ThrottleEntry>,
+}
+
+impl NotificationThrottle {
+    pub(crate) fn record(
+        &mut self,
+        service_id: &str,
+        new_msgs: u32,
+        min_interval: Duration,
+        now: Instant,
+    ) -> ThrottleDecision {
+        let entry = self.entries.entry(service_id.to_string()).or_default();
+        match entry.last_shown.map(|shown| shown + min_interval) {
+            Some(ready_at) if now < ready_at => {
+                entry.pending = entry.pending.saturating_add(new_msgs);
+                if entry.flush_scheduled {
+                    ThrottleDecision::Merged
+                } else {
+                    entry.flush_scheduled = true;
+                    ThrottleDecision::Defer(ready_at - now)
+                }
+            }
+            _ => {
+                // A late timer may still be pending: this notification covers
+                // its messages, the flush will find nothing left.
+                let count = new_msgs.saturating_add(std::mem::take(&mut entry.pending));
+                entry.held = None;
+                entry.last_shown = Some(now);
+                ThrottleDecision::Show(count)
+            }
+        }
+    }
+
+    /// Keep `notification` (just deferred or merged) to be shown by the flush,
+    /// in place of any older one.
+    pub(crate) fn hold(&mut self, service_id: &str, notification: HeldNotification) {
+        if let Some(entry) = self.entries.get_mut(service_id) {
+            if entry.pending > 0 {
+                entry.held = Some(notification);
+            }
+        }
+    }
+
+    /// The web notification to show for the messages [`flush`] just
+    /// returned, if one of them was.
+    ///
+    /// [`flush`]: NotificationThrottle::flush
+    pub(crate) fn take_held(&mut self, service_id: &str) -> Option<HeldNotification> {
+        self.entries.get_mut(service_id)?.held.take()
+    }
+
+    /// End of a deferral: the merged count to notify, if anything is left.
+    pub(crate) fn flush(&mut self, service_id: &str, now: Instant) -> Option<u32> {
+        let entry = self.entries.get_mut(service_id)?;
+        entry.flush_scheduled = false;
+        if entry.pending == 0 {
+            return None;
+        }
+        entry.last_shown = Some(now);
+        Some(std::mem::take(&mut entry.pending))
+    }
+
+    /// Drop held-back messages (the user has read everything meanwhile).
+    pub(crate) fn discard_pending(&mut self, service_id: &str) {
+        if let Some(entry) = self.entries.get_mut(service_id) {
+            entry.pending = 0;
+            entry.held = None;
+        }
+    }
+
+    pub(crate) fn forget_service(&mut self, service_id: &str) {
+        self.entries.remove(service_id);
+    }
+
+    /// Follow a service id rename. A flush timer still running under the old
+    /// id finds nothing: the held-back messages go out with the service's
+    /// next notification instead.
+    pub(crate) fn rename_service(&mut self, old_id: &str, new_id: &str) {
+        if let Some(mut entry) = self.entries.remove(old_id) {
+            entry.flush_scheduled = false;
+            self.entries.insert(new_id.to_string(), entry);
+        }
+    }
+}
+
 /// Reflect the total unread count on the app's taskbar icon.
 ///
 /// Windows has no numeric taskbar badge, so an overlay dot is shown while there
@@ -103,7 +235,7 @@ pub fn update_taskbar_indicator(app: &AppHandle, total: u32) {
 
 /// Briefly flash / highlight the taskbar entry to signal a new notification.
 /// No-op on the focused window on most platforms.
-fn flash_taskbar(app: &AppHandle) {
+pub(crate) fn flash_taskbar(app: &AppHandle) {
     if let Some(window) = app.get_window("main") {
         let _ = window.request_user_attention(Some(tauri::UserAttentionType::Informational));
     }
@@ -139,15 +271,65 @@ fn unread_overlay_icon() -> tauri::image::Image<'static> {
     tauri::image::Image::new(rgba, SIZE, SIZE)
 }
 
+/// Unread counts of one service. `direct` is what concerns the user (mentions,
+/// DMs, and any count read from the page title); `indirect` is background
+/// activity such as unread channel messages, shown as a dot in the sidebar.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct BadgeCount {
+    pub direct: u32,
+    pub indirect: u32,
+}
+
+impl BadgeCount {
+    pub fn is_empty(&self) -> bool {
+        self.direct == 0 && self.indirect == 0
+    }
+
+    pub fn total(&self) -> u32 {
+        self.direct.saturating_add(self.indirect)
+    }
+
+    /// What counts outside the sidebar (notifications, taskbar): direct
+    /// counts only for a `notify_direct_only` service, everything otherwise.
+    pub fn counted(&self, direct_only: bool) -> u32 {
+        if direct_only {
+            self.direct
+        } else {
+            self.total()
+        }
+    }
+}
+
+/// Ids of the services whose indirect activity stays in the sidebar.
+fn direct_only_ids(services: &[Service]) -> HashSet<String> {
+    services
+        .iter()
+        .filter(|s| s.notify_direct_only)
+        .map(|s| s.id.clone())
+        .collect()
+}
+
+/// Taskbar total: the sum of every service's [`BadgeCount::counted`].
+fn taskbar_total(badges: &HashMap<String, BadgeCount>, direct_only: &HashSet<String>) -> u32 {
+    badges
+        .iter()
+        .map(|(id, b)| b.counted(direct_only.contains(id)))
+        .fold(0, u32::saturating_add)
+}
+
 /// Clear now-muted services from the badge map and return the new taskbar total.
 /// Locks are taken sequentially (never nested) to avoid deadlocks.
 fn refresh_badges_for_levels(state: &WebviewState) -> u32 {
-    let off_ids: Vec<String> = match state.services.lock() {
-        Ok(services) => services
-            .iter()
-            .filter(|s| s.notify_level() == NOTIFY_OFF)
-            .map(|s| s.id.clone())
-            .collect(),
+    let (weekday, minute) = notifications::local_time();
+    let (off_ids, direct_only): (Vec<String>, _) = match state.services.lock() {
+        Ok(services) => (
+            services
+                .iter()
+                .filter(|s| s.notify_level_at(weekday, minute) == NOTIFY_OFF)
+                .map(|s| s.id.clone())
+                .collect(),
+            direct_only_ids(&services),
+        ),
         Err(e) => {
             eprintln!("[Taurium] Mutex poisoned: {}", e);
             return 0;
@@ -158,7 +340,7 @@ fn refresh_badges_for_levels(state: &WebviewState) -> u32 {
             for id in &off_ids {
                 badges.remove(id);
             }
-            badges.values().copied().sum()
+            taskbar_total(&badges, &direct_only)
         }
         Err(e) => {
             eprintln!("[Taurium] Mutex poisoned: {}", e);
@@ -167,53 +349,229 @@ fn refresh_badges_for_levels(state: &WebviewState) -> u32 {
     }
 }
 
-/// Drop ids belonging to "keep alive" services from hibernation candidates.
-pub(crate) fn filter_hibernation_candidates(
-    navigated_ids: &HashSet<String>,
-    keep_alive_ids: &HashSet<String>,
-) -> Vec<String> {
-    navigated_ids
+/// Idle threshold (seconds) of every service that hibernates on a timer,
+/// from its policy and the global `hibernation_minutes`. Services that never
+/// hibernate are left out.
+pub(crate) fn hibernation_thresholds(
+    services: &[Service],
+    default_minutes: u32,
+) -> HashMap<String, u64> {
+    services
         .iter()
-        .filter(|id| !keep_alive_ids.contains(*id))
-        .cloned()
+        .filter_map(|s| {
+            s.hibernation
+                .idle_threshold_secs(default_minutes)
+                .map(|secs| (s.id.clone(), secs))
+        })
         .collect()
 }
 
-/// Select navigated webview ids that exceeded the inactivity threshold (excluding the active one).
+/// Select navigated webview ids that exceeded their inactivity threshold
+/// (excluding the active one). Ids without a threshold never hibernate.
 pub(crate) fn select_webviews_to_hibernate(
     navigated_ids: &[String],
     active_id: Option<&str>,
     last_activity: &HashMap<String, Instant>,
     now: Instant,
-    hibernation_secs: u64,
+    thresholds_secs: &HashMap<String, u64>,
 ) -> Vec<String> {
     navigated_ids
         .iter()
         .filter(|id| active_id != Some(id.as_str()))
         .filter(|id| {
-            last_activity
-                .get(*id)
-                .is_some_and(|last| now.duration_since(*last).as_secs() > hibernation_secs)
+            let (Some(last), Some(threshold)) = (last_activity.get(*id), thresholds_secs.get(*id))
+            else {
+                return false;
+            };
+            now.duration_since(*last).as_secs() > *threshold
         })
         .cloned()
         .collect()
 }
 
+/// Under memory pressure: up to `count` navigated webview ids, least recently
+/// used first, whatever their idle time. The active one and `exempt_ids`
+/// (services that never hibernate) are kept; ids never shown come first.
+pub(crate) fn select_lru_to_hibernate(
+    navigated_ids: &[String],
+    active_id: Option<&str>,
+    last_activity: &HashMap<String, Instant>,
+    exempt_ids: &HashSet<String>,
+    count: usize,
+) -> Vec<String> {
+    let mut candidates: Vec<&String> = navigated_ids
+        .iter()
+        .filter(|id| active_id != Some(id.as_str()) && !exempt_ids.contains(*id))
+        .collect();
+    candidates.sort_by(|a, b| {
+        last_activity
+            .get(*a)
+            .cmp(&last_activity.get(*b))
+            .then_with(|| a.cmp(b))
+    });
+    candidates.into_iter().take(count).cloned().collect()
+}
+
+/// Load status reported by [`service_resource_usage`]: `"navigated"` (page
+/// loaded), `"hibernated"` (webview closed by hibernation) or `"not_loaded"`
+/// (never shown since startup, or a blank webview).
+fn service_load_status(
+    id: &str,
+    created_ids: &[String],
+    navigated: &HashSet<String>,
+    hibernated: &HashSet<String>,
+) -> &'static str {
+    let created = created_ids.iter().any(|c| c == id);
+    if created && navigated.contains(id) {
+        "navigated"
+    } else if !created && hibernated.contains(id) {
+        "hibernated"
+    } else {
+        "not_loaded"
+    }
+}
+
 pub struct WebviewState {
     pub created_ids: Mutex<Vec<String>>,
     pub active_id: Mutex<Option<String>>,
-    pub app_data_dir: PathBuf,
+    /// Where profiles.json and the profiles live (app data or portable dir).
+    pub data_root: PathBuf,
+    /// Directory of the active profile (see [`config::get_profile_dir`]);
+    /// changes when switching profiles.
+    pub app_data_dir: Mutex<PathBuf>,
     pub services: Mutex<Vec<Service>>,
     /// Tracks which webviews have been navigated to their real URL
     pub navigated: Mutex<HashSet<String>>,
+    /// Services whose webview was closed by hibernation, until it is
+    /// recreated or the service removed.
+    pub hibernated: Mutex<HashSet<String>>,
+    /// Last meaningful page loaded by each webview, saved to state.json when
+    /// it is hibernated.
+    pub page_urls: Mutex<HashMap<String, String>>,
+    /// Page and `window.scrollY` last reported by each webview (see
+    /// [`SCROLL_REPORTER`]), saved along with its page.
+    pub scroll_positions: Mutex<HashMap<String, (String, u32)>>,
+    /// Scroll position to reapply once a woken-up service has loaded its
+    /// last page.
+    pub pending_scroll: Mutex<HashMap<String, u32>>,
     /// Last time each webview was actively shown
     pub last_activity: Mutex<HashMap<String, Instant>>,
     /// Badge counts per service id
-    pub badge_counts: Mutex<HashMap<String, u32>>,
+    pub badge_counts: Mutex<HashMap<String, BadgeCount>>,
     /// Current sidebar width in logical px (compact or expanded).
     pub sidebar_width: Mutex<f64>,
     /// Warnings/errors from the initial services.json load (read-only after setup).
     pub services_load_info: ServicesLoadInfo,
+    /// In-memory copy of preferences.json, kept in sync by every path that
+    /// saves or reloads it (hot paths like title changes never hit the disk).
+    pub preferences: Mutex<Preferences>,
+    /// Services whose page has shown a web notification (see
+    /// `notifications.rs`); their badge changes no longer notify.
+    pub notification_api_ids: Mutex<HashSet<String>>,
+    /// Rate limit of badge notifications (see [`NotificationThrottle`]).
+    pub notification_throttle: Mutex<NotificationThrottle>,
+}
+
+/// Resources and load status of one service.
+#[derive(Debug, Clone, Serialize)]
+pub struct ServiceResourceUsage {
+    pub id: String,
+    /// See [`service_load_status`].
+    pub status: &'static str,
+    /// Processes working from the service's data directory (on WebKitGTK,
+    /// its network process, not the web process rendering the page); `None`
+    /// where they can't be read (only Linux is supported).
+    pub usage: Option<memory_monitor::ProcessUsage>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResourceUsageReport {
+    pub services: Vec<ServiceResourceUsage>,
+    /// Taurium itself and the processes no service could be matched with,
+    /// including the web processes of every service.
+    pub other: Option<memory_monitor::ProcessUsage>,
+}
+
+/// Per-service resource usage. Each service has its own data directory, so
+/// its own network process, found through `/proc` (see
+/// [`memory_monitor::usage_by_data_dir`]); page rendering is only in
+/// `other`. Reads every process of the tree: not for the main thread.
+pub fn service_resource_usage(state: &WebviewState) -> Result<ResourceUsageReport, TauriumError> {
+    let data_dir = current_data_dir(state);
+    let services: Vec<String> = state
+        .services
+        .lock()
+        .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))?
+        .iter()
+        .map(|s| s.id.clone())
+        .collect();
+    let statuses: Vec<&'static str> = {
+        let created = state
+            .created_ids
+            .lock()
+            .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))?;
+        let navigated = state
+            .navigated
+            .lock()
+            .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))?;
+        let hibernated = state
+            .hibernated
+            .lock()
+            .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))?;
+        services
+            .iter()
+            .map(|id| service_load_status(id, &created, &navigated, &hibernated))
+            .collect()
+    };
+
+    let usage = memory_monitor::read_process_samples().map(|samples| {
+        let data_dirs: Vec<(String, PathBuf)> = services
+            .iter()
+            .map(|id| (id.clone(), config::get_webview_data_dir(&data_dir, id)))
+            .collect();
+        memory_monitor::usage_by_data_dir(&samples, &data_dirs)
+    });
+    let services = services
+        .into_iter()
+        .zip(statuses)
+        .map(|(id, status)| ServiceResourceUsage {
+            usage: usage
+                .as_ref()
+                .map(|(per_service, _)| per_service.get(&id).copied().unwrap_or_default()),
+            id,
+            status,
+        })
+        .collect();
+    Ok(ResourceUsageReport {
+        services,
+        other: usage.map(|(_, other)| other),
+    })
+}
+
+/// Directory of the active profile. A poisoned lock still holds a valid path.
+pub fn current_data_dir(state: &WebviewState) -> PathBuf {
+    match state.app_data_dir.lock() {
+        Ok(dir) => dir.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    }
+}
+
+/// Current preferences, falling back to the defaults if the lock is poisoned.
+pub fn current_preferences(state: &WebviewState) -> Preferences {
+    state
+        .preferences
+        .lock()
+        .map(|p| p.clone())
+        .unwrap_or_default()
+}
+
+/// Replace the cached preferences (after saving or reloading preferences.json).
+pub fn set_preferences(state: &WebviewState, prefs: Preferences) -> Result<(), TauriumError> {
+    *state
+        .preferences
+        .lock()
+        .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))? = prefs;
+    Ok(())
 }
 
 /// Current sidebar width, falling back to the compact width if the lock is poisoned.
@@ -238,42 +596,82 @@ fn notify_service_loaded(app: &AppHandle, service_id: &str) {
     }
 }
 
-/// Handle document title change: update badge count, send notification, refresh sidebar
-pub fn handle_title_change(app: &AppHandle, service_id: &str, service_name: &str, title: &str) {
-    // Skip blank/empty pages (avoid unnecessary work during webview creation)
-    if title.is_empty() || title == "about:blank" {
-        return;
-    }
+/// Result of applying a title change to the badge map.
+struct BadgeUpdate {
+    prev_count: BadgeCount,
+    count: BadgeCount,
+    badges_json: String,
+    total: u32,
+    notify_allowed: bool,
+    /// The service's `notify_direct_only` setting.
+    direct_only: bool,
+}
 
-    notify_service_loaded(app, service_id);
+impl BadgeUpdate {
+    /// `(count, prev_count)` as seen by notifications (see
+    /// [`BadgeCount::counted`]).
+    fn notified_counts(&self) -> (u32, u32) {
+        (
+            self.count.counted(self.direct_only),
+            self.prev_count.counted(self.direct_only),
+        )
+    }
+}
 
-    let state = app.state::<WebviewState>();
+/// Update the badge map from a new document title. State only: no UI or
+/// filesystem access, so it runs on every title change of every service.
+fn apply_title_to_badges(
+    state: &WebviewState,
+    service_id: &str,
+    title: &str,
+) -> Option<BadgeUpdate> {
+    // Titles carry a single number, which is what the user is expected to
+    // read: count it as direct.
+    let count = BadgeCount {
+        direct: extract_badge_count(title),
+        indirect: 0,
+    };
+    eprintln!(
+        "[Taurium] Title changed: '{}' → badge count: {} (service: {})",
+        title, count.direct, service_id
+    );
+    apply_badge_count(state, service_id, count)
+}
 
+/// Store `count` as the unread count of `service_id`, whatever its source
+/// (document title or recipe badge script). State only, like
+/// [`apply_title_to_badges`].
+fn apply_badge_count(
+    state: &WebviewState,
+    service_id: &str,
+    count: BadgeCount,
+) -> Option<BadgeUpdate> {
     // Per-service notification level: "all" (notify + badge), "badge" (silent
-    // unread badge) or "off" (fully muted). Absent/unknown falls back to "all".
-    let level = match state.services.lock() {
-        Ok(services) => services
-            .iter()
-            .find(|s| s.id == service_id)
-            .map(|s| s.notify_level())
-            .unwrap_or(NOTIFY_ALL),
+    // unread badge) or "off" (fully muted), possibly set by the service's
+    // schedule for the current local time. Absent/unknown falls back to "all".
+    let (weekday, minute) = notifications::local_time();
+    let (level, direct_only, direct_only_services) = match state.services.lock() {
+        Ok(services) => {
+            let (level, direct_only) = services
+                .iter()
+                .find(|s| s.id == service_id)
+                .map(|s| (s.notify_level_at(weekday, minute), s.notify_direct_only))
+                .unwrap_or((NOTIFY_ALL, false));
+            (level, direct_only, direct_only_ids(&services))
+        }
         Err(e) => {
             eprintln!("[Taurium] Mutex poisoned: {}", e);
-            return;
+            return None;
         }
     };
 
     // A muted service keeps no badge and is excluded from the taskbar total:
     // forcing the count to 0 removes it from the badge map below.
     let count = if level == NOTIFY_OFF {
-        0
+        BadgeCount::default()
     } else {
-        extract_badge_count(title)
+        count
     };
-    eprintln!(
-        "[Taurium] Title changed: '{}' → badge count: {} (service: {}, notify: {})",
-        title, count, service_id, level
-    );
 
     // Update badge counts and compute the taskbar total (hold lock briefly).
     let (prev_count, badges_json, total) = {
@@ -281,43 +679,92 @@ pub fn handle_title_change(app: &AppHandle, service_id: &str, service_name: &str
             Ok(guard) => guard,
             Err(e) => {
                 eprintln!("[Taurium] Mutex poisoned: {}", e);
-                return;
+                return None;
             }
         };
-        let prev = badges.get(service_id).copied().unwrap_or(0);
-        if count > 0 {
-            badges.insert(service_id.to_string(), count);
-        } else {
+        let prev = badges.get(service_id).copied().unwrap_or_default();
+        if count.is_empty() {
             badges.remove(service_id);
+        } else {
+            badges.insert(service_id.to_string(), count);
         }
-        let total: u32 = badges.values().copied().sum();
+        let total = taskbar_total(&badges, &direct_only_services);
         let json = serde_json::to_string(&*badges).unwrap_or_default();
         (prev, json, total)
     }; // badge_counts lock released here
 
     // Desktop notification: only for "all" services (and when the global switch
     // is on). "badge" services increment the badge silently.
-    let prefs = load_preferences(&state.app_data_dir);
-    let notify_allowed = prefs.notifications_enabled && level == NOTIFY_ALL;
+    let notify_allowed = current_preferences(state).notifications_enabled && level == NOTIFY_ALL;
+
+    Some(BadgeUpdate {
+        prev_count,
+        count,
+        badges_json,
+        total,
+        notify_allowed,
+        direct_only,
+    })
+}
+
+/// Badge strategy of the recipe matching the service's URL, if any.
+fn service_badge_strategy(
+    state: &WebviewState,
+    service_id: &str,
+) -> Option<&'static recipes::BadgeStrategy> {
+    let services = state.services.lock().ok()?;
+    let service = services.iter().find(|s| s.id == service_id)?;
+    recipes::badge_strategy_for_url(&service.url)
+}
+
+/// Desktop notification, sidebar badges and taskbar indicator for a badge
+/// change. The notification is only a fallback for pages that never use the
+/// web Notification API.
+fn publish_badge_update(
+    app: &AppHandle,
+    service_id: &str,
+    service_name: &str,
+    update: BadgeUpdate,
+) {
+    let (count, prev_count) = update.notified_counts();
+    let BadgeUpdate {
+        badges_json,
+        total,
+        notify_allowed,
+        ..
+    } = update;
+    let uses_notification_api = app
+        .state::<WebviewState>()
+        .notification_api_ids
+        .lock()
+        .map(|ids| ids.contains(service_id))
+        .unwrap_or(false);
+    let notify_allowed = notify_allowed && !uses_notification_api;
+
     if let Some(body) =
         notification_body_for_badge_change(service_name, count, prev_count, notify_allowed)
     {
-        eprintln!(
-            "[Taurium] Sending notification: {} - {}",
-            service_name, body
-        );
-        match app
-            .notification()
-            .builder()
-            .title(service_name)
-            .body(&body)
-            .show()
-        {
-            Ok(_) => eprintln!("[Taurium] Notification sent successfully"),
-            Err(e) => eprintln!("[Taurium] Notification error: {}", e),
+        let new_msgs = count - prev_count;
+        match throttle_badge_notification(app, service_id, new_msgs) {
+            ThrottleDecision::Show(n) => {
+                let body = if n == new_msgs {
+                    body
+                } else {
+                    new_notifications_body(service_name, n)
+                };
+                eprintln!(
+                    "[Taurium] Sending notification: {} - {}",
+                    service_name, body
+                );
+                emit_badge_notification(app, service_id, service_name, &body, count);
+            }
+            ThrottleDecision::Defer(delay) => schedule_throttled_flush(app, service_id, delay),
+            ThrottleDecision::Merged => {}
+        }
+    } else if count == 0 {
+        if let Ok(mut throttle) = app.state::<WebviewState>().notification_throttle.lock() {
+            throttle.discard_pending(service_id);
         }
-        // Draw attention by briefly flashing the taskbar entry.
-        flash_taskbar(app);
     }
 
     // Update sidebar badges (lock already released, safe to eval)
@@ -331,6 +778,158 @@ pub fn handle_title_change(app: &AppHandle, service_id: &str, service_name: &str
 
     // Reflect the total unread count on the app's taskbar icon.
     update_taskbar_indicator(app, total);
+}
+
+fn throttle_badge_notification(
+    app: &AppHandle,
+    service_id: &str,
+    new_msgs: u32,
+) -> ThrottleDecision {
+    let state = app.state::<WebviewState>();
+    let min_interval = Duration::from_secs(u64::from(
+        current_preferences(&state).notification_interval_seconds,
+    ));
+    match state.notification_throttle.lock() {
+        Ok(mut throttle) => throttle.record(service_id, new_msgs, min_interval, Instant::now()),
+        // Never lose a notification to a poisoned lock.
+        Err(_) => ThrottleDecision::Show(new_msgs),
+    }
+}
+
+/// Rate limit for a notification sent by the page through the web
+/// Notification API: whether to show it now. A held-back one is kept for the
+/// end of the interval, where the latest one is shown.
+pub(crate) fn throttle_web_notification(
+    app: &AppHandle,
+    service_id: &str,
+    notification: HeldNotification,
+) -> bool {
+    match throttle_badge_notification(app, service_id, 1) {
+        ThrottleDecision::Show(_) => return true,
+        ThrottleDecision::Defer(delay) => schedule_throttled_flush(app, service_id, delay),
+        ThrottleDecision::Merged => {}
+    }
+    if let Ok(mut throttle) = app.state::<WebviewState>().notification_throttle.lock() {
+        throttle.hold(service_id, notification);
+    }
+    false
+}
+
+/// Show the merged notification of a throttled service once its interval is
+/// over. Dropped if the profile was switched meanwhile (the messages belonged
+/// to the old profile's service), or if notifications were turned off for
+/// the service since.
+fn schedule_throttled_flush(app: &AppHandle, service_id: &str, delay: Duration) {
+    let app = app.clone();
+    let service_id = service_id.to_string();
+    let data_dir = current_data_dir(&app.state::<WebviewState>());
+    std::thread::spawn(move || {
+        std::thread::sleep(delay);
+        let state = app.state::<WebviewState>();
+        if current_data_dir(&state) != data_dir {
+            return;
+        }
+        let pending = match state.notification_throttle.lock() {
+            Ok(mut throttle) => throttle
+                .flush(&service_id, Instant::now())
+                .map(|new_msgs| (new_msgs, throttle.take_held(&service_id))),
+            Err(_) => None,
+        };
+        let Some((new_msgs, held)) = pending else {
+            return;
+        };
+        // Renamed or removed meanwhile: use the current name, or drop it.
+        let (weekday, minute) = notifications::local_time();
+        let service = match state.services.lock() {
+            Ok(services) => services.iter().find(|s| s.id == service_id).map(|s| {
+                (
+                    s.name.clone(),
+                    s.notify_direct_only,
+                    s.notify_level_at(weekday, minute),
+                )
+            }),
+            Err(_) => None,
+        };
+        let Some((service_name, direct_only, level)) = service else {
+            return;
+        };
+        if !current_preferences(&state).notifications_enabled || level != NOTIFY_ALL {
+            return;
+        }
+        if let Some(held) = held {
+            eprintln!("[Taurium] Sending held web notification: {}", held.title);
+            notifications::show_service_notification(
+                &app,
+                &service_id,
+                &held.title,
+                &held.body,
+                held.icon.as_deref(),
+            );
+            return;
+        }
+        // Same count as the notifications shown right away.
+        let count = state
+            .badge_counts
+            .lock()
+            .ok()
+            .and_then(|badges| badges.get(&service_id).map(|b| b.counted(direct_only)))
+            .unwrap_or(new_msgs);
+        let body = new_notifications_body(&service_name, new_msgs);
+        eprintln!("[Taurium] Sending merged notification: {service_name} - {body}");
+        emit_badge_notification(&app, &service_id, &service_name, &body, count);
+    });
+}
+
+/// Show a badge notification and keep it in the recent activity log, unless
+/// it wasn't shown (Do Not Disturb).
+fn emit_badge_notification(
+    app: &AppHandle,
+    service_id: &str,
+    service_name: &str,
+    body: &str,
+    count: u32,
+) {
+    if !notifications::show_service_notification(app, service_id, service_name, body, None) {
+        return;
+    }
+    let record = NotificationRecord {
+        service_id: service_id.to_string(),
+        time: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0),
+        body: body.to_string(),
+        count,
+    };
+    let data_dir = current_data_dir(&app.state::<WebviewState>());
+    if let Err(e) = notification_history::append_record(&data_dir, record) {
+        eprintln!("[Taurium] Failed to record notification: {e}");
+        return;
+    }
+    if let Some(settings) = app.get_webview("settings") {
+        settings
+            .eval("window.__reloadNotificationHistory && window.__reloadNotificationHistory()")
+            .ok();
+    }
+}
+
+/// Handle document title change: update badge count, send notification, refresh sidebar
+pub fn handle_title_change(app: &AppHandle, service_id: &str, service_name: &str, title: &str) {
+    // Skip blank/empty pages (avoid unnecessary work during webview creation)
+    if title.is_empty() || title == "about:blank" {
+        return;
+    }
+
+    notify_service_loaded(app, service_id);
+
+    let state = app.state::<WebviewState>();
+    // Services with a recipe badge strategy report their count from the page
+    // (handle_badge_report); their title would only fight with it.
+    if service_badge_strategy(&state, service_id).is_none() {
+        if let Some(update) = apply_title_to_badges(&state, service_id, title) {
+            publish_badge_update(app, service_id, service_name, update);
+        }
+    }
 
     // Per-service zoom once the remote document is live (title updates after load)
     let zoom = {
@@ -351,6 +950,83 @@ pub fn handle_title_change(app: &AppHandle, service_id: &str, service_name: &str
     }
 }
 
+/// Injected in every service webview: reports the page's scroll position
+/// once scrolling settles, so hibernation can save it with the page.
+pub(crate) const SCROLL_REPORTER: &str = r#"
+(function () {
+  if (window.top !== window) return;
+  var timer = null;
+  window.addEventListener("scroll", function () {
+    clearTimeout(timer);
+    timer = setTimeout(function () {
+      var ipc = window.__TAURI_INTERNALS__;
+      if (!ipc) return;
+      ipc.invoke("report_scroll_position", {
+        url: location.href,
+        y: Math.max(0, Math.round(window.scrollY))
+      }).catch(function () {});
+    }, 500);
+  }, { passive: true });
+})();
+"#;
+
+/// Handle a scroll position reported by [`SCROLL_REPORTER`].
+pub fn handle_scroll_report(app: &AppHandle, service_id: &str, url: String, y: u32) {
+    let state = app.state::<WebviewState>();
+    // Popups and the sidebar/settings webviews are never hibernated.
+    let known = state
+        .services
+        .lock()
+        .map(|services| services.iter().any(|s| s.id == service_id))
+        .unwrap_or(false);
+    if !known {
+        return;
+    }
+    if let Ok(mut positions) = state.scroll_positions.lock() {
+        positions.insert(service_id.to_string(), (url, y));
+    }
+}
+
+/// Scroll back to `y` once the page is tall enough: single-page apps render
+/// their content a little after the load event.
+pub(crate) fn scroll_restore_js(y: u32) -> String {
+    format!(
+        "(function(){{var tries=0;(function attempt(){{window.scrollTo(0,{y});\
+         if(Math.abs(window.scrollY-{y})>1&&++tries<20)setTimeout(attempt,100);}})();}})()"
+    )
+}
+
+/// Handle an unread count reported by a recipe badge script (see
+/// `recipes::badge_script`): same badge path as title changes.
+pub fn handle_badge_report(app: &AppHandle, service_id: &str, count: BadgeCount) {
+    let state = app.state::<WebviewState>();
+    let service_name = match state.services.lock() {
+        Ok(services) => services
+            .iter()
+            .find(|s| s.id == service_id)
+            .map(|s| s.name.clone()),
+        Err(e) => {
+            eprintln!("[Taurium] Mutex poisoned: {}", e);
+            return;
+        }
+    };
+    // Popups and the sidebar/settings webviews have no badge.
+    let Some(service_name) = service_name else {
+        return;
+    };
+    let count = BadgeCount {
+        direct: count.direct.min(MAX_BADGE_COUNT),
+        indirect: count.indirect.min(MAX_BADGE_COUNT),
+    };
+    eprintln!(
+        "[Taurium] Badge script reported {} direct / {} indirect unread (service: {})",
+        count.direct, count.indirect, service_id
+    );
+    if let Some(update) = apply_badge_count(&state, service_id, count) {
+        publish_badge_update(app, service_id, &service_name, update);
+    }
+}
+
 /// Reload a service webview by navigating it back to its configured URL.
 pub fn reload_service_webview(
     app: &AppHandle,
@@ -529,6 +1205,57 @@ fn create_popup_window(
     }
 }
 
+/// Build the scripts injecting a service's custom CSS and JS, one per
+/// customization so a broken user script can't take the style (or the
+/// app's own shims) down with it. They run as initialization scripts
+/// (before the page's own scripts, so the DOM may not exist yet) and again
+/// on every finished load: the style element is created or updated in
+/// place, and the user script runs at most once per document.
+pub(crate) fn custom_injection_scripts(css: Option<&str>, js: Option<&str>) -> Vec<String> {
+    let mut scripts = Vec::new();
+    if let Some(css) = css.filter(|c| !c.trim().is_empty()) {
+        if let Ok(css_json) = serde_json::to_string(css) {
+            scripts.push(format!(
+                r#"(function(){{
+var css = {css_json};
+function applyCss() {{
+  var root = document.head || document.documentElement;
+  if (!root) return false;
+  var style = document.getElementById("__taurium_custom_css");
+  if (!style) {{
+    style = document.createElement("style");
+    style.id = "__taurium_custom_css";
+  }}
+  if (style.textContent !== css) style.textContent = css;
+  if (style.parentNode !== root) root.appendChild(style);
+  return true;
+}}
+if (!applyCss()) document.addEventListener("readystatechange", applyCss, {{ once: true }});
+document.addEventListener("DOMContentLoaded", applyCss);
+}})();"#
+            ));
+        }
+    }
+    if let Some(js) = js.filter(|j| !j.trim().is_empty()) {
+        // The user's code is pasted as is (eval / new Function would trip
+        // the page's CSP), so it stays alone in its script.
+        scripts.push(format!(
+            r#"(function(){{
+function runJs() {{
+  if (window.__tauriumCustomJsRan) return;
+  window.__tauriumCustomJsRan = true;
+  try {{
+{js}
+  }} catch (e) {{ console.error("[Taurium] custom script failed", e); }}
+}}
+if (document.readyState === "loading") document.addEventListener("DOMContentLoaded", runJs);
+else runJs();
+}})();"#
+        ));
+    }
+    scripts
+}
+
 fn create_service_webview_inner(
     app: &AppHandle,
     window: &tauri::Window,
@@ -544,6 +1271,11 @@ fn create_service_webview_inner(
     let sid_for_load = service.id.clone();
     let sname = service.name.clone();
     let state = app.state::<WebviewState>();
+    let custom_scripts =
+        custom_injection_scripts(service.custom_css.as_deref(), service.custom_js.as_deref());
+    let custom_for_load = custom_scripts.clone();
+    let badge_script =
+        recipes::badge_strategy_for_url(&service.url).and_then(recipes::badge_script);
 
     // Idempotency guard. `app.get_webview(id)` — checked off the main thread
     // in switch_to — can briefly return None even though the label is already
@@ -568,17 +1300,34 @@ fn create_service_webview_inner(
         return Ok(());
     }
 
-    let data_dir = state.app_data_dir.join("webview_data").join(&service.id);
+    let data_dir = config::get_webview_data_dir(&current_data_dir(state), &service.id);
     fs::create_dir_all(&data_dir)?;
 
     // Les services embarqués (Slack, etc.) utilisent souvent l’API HTML5
     // drag-and-drop ; le handler natif Tauri bloque ces événements DOM.
     let builder = tauri::webview::WebviewBuilder::new(&service.id, url)
         .disable_drag_drop_handler()
-        .on_page_load(move |_wv, payload| {
+        .on_page_load(move |wv, payload| {
             if payload.event() == PageLoadEvent::Finished
                 && is_meaningful_page_url(payload.url().as_str())
             {
+                let load_state = app_for_load.state::<WebviewState>();
+                if let Ok(mut urls) = load_state.page_urls.lock() {
+                    urls.insert(sid_for_load.clone(), payload.url().to_string());
+                }
+                let pending_scroll = load_state
+                    .pending_scroll
+                    .lock()
+                    .ok()
+                    .and_then(|mut pending| pending.remove(&sid_for_load));
+                if let Some(y) = pending_scroll {
+                    wv.eval(scroll_restore_js(y)).ok();
+                }
+                // Pages that rebuild <head> (SPA shells) drop the injected
+                // style; the scripts are idempotent, so just run them again.
+                for script in &custom_for_load {
+                    wv.eval(script).ok();
+                }
                 notify_service_loaded(&app_for_load, &sid_for_load);
             }
         })
@@ -607,6 +1356,17 @@ fn create_service_webview_inner(
     } else {
         builder
     };
+    let builder = custom_scripts.iter().fold(builder, |builder, script| {
+        builder.initialization_script(script)
+    });
+    let builder = if let Some(ref script) = badge_script {
+        builder.initialization_script(script)
+    } else {
+        builder
+    };
+    let builder = builder
+        .initialization_script(notifications::NOTIFICATION_SHIM)
+        .initialization_script(SCROLL_REPORTER);
     #[cfg(any(target_os = "windows", target_os = "linux"))]
     let builder = builder.data_directory(data_dir.clone());
 
@@ -623,6 +1383,11 @@ fn create_service_webview_inner(
         .lock()
         .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))?
         .push(service.id.clone());
+    state
+        .hibernated
+        .lock()
+        .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))?
+        .remove(&service.id);
 
     eprintln!("[Taurium] Webview '{}' created (hidden, lazy)", service.id);
     Ok(())
@@ -743,22 +1508,42 @@ fn ensure_navigated(app: &AppHandle, state: &WebviewState, id: &str) {
     }
 
     // Find service URL
-    let services = match state.services.lock() {
-        Ok(guard) => guard,
+    let home = match state.services.lock() {
+        Ok(services) => services.iter().find(|s| s.id == id).map(|s| s.url.clone()),
         Err(e) => {
             eprintln!("[Taurium] Mutex poisoned: {}", e);
             return;
         }
     };
-    if let Some(service) = services.iter().find(|s| s.id == id) {
-        if let Some(webview) = app.get_webview(id) {
-            let url = service.url.clone();
-            eprintln!("[Taurium] Lazy-loading {} -> {}", id, url);
-            let js = window_location_replace_js(&url);
-            webview.eval(&js).ok();
-            navigated.insert(id.to_string());
+    let (Some(home), Some(webview)) = (home, app.get_webview(id)) else {
+        return;
+    };
+    // Claimed before the lock is released so a concurrent switch doesn't
+    // navigate twice; state.json is read without holding it.
+    navigated.insert(id.to_string());
+    drop(navigated);
+
+    // Back from hibernation: reopen the page the service was on. With the
+    // option off, the page stays recorded.
+    let last_page = if current_preferences(state).restore_last_page {
+        config::take_last_page(&current_data_dir(state), id, &home)
+    } else {
+        None
+    };
+    let url = match last_page {
+        Some(page) => {
+            if page.scroll_y > 0 {
+                if let Ok(mut pending) = state.pending_scroll.lock() {
+                    pending.insert(id.to_string(), page.scroll_y);
+                }
+            }
+            page.url
         }
-    }
+        None => home,
+    };
+    eprintln!("[Taurium] Lazy-loading {} -> {}", id, url);
+    let js = window_location_replace_js(&url);
+    webview.eval(&js).ok();
 }
 
 pub(crate) fn window_location_replace_js(url: &str) -> String {
@@ -798,6 +1583,7 @@ pub fn apply_zoom_from_state(app: &AppHandle, state: &WebviewState, id: &str) {
 
 pub fn switch_to(app: &AppHandle, state: &WebviewState, id: &str) -> Result<(), TauriumError> {
     eprintln!("[Taurium] Switching to service: {}", id);
+    let previous = active_service_id(state);
 
     // Create the webview on demand if it doesn't exist yet (e.g. a service
     // added after startup). Callers of switch_to run off the main thread
@@ -856,11 +1642,40 @@ pub fn switch_to(app: &AppHandle, state: &WebviewState, id: &str) -> Result<(),
         .insert(id.to_string(), Instant::now());
 
     eprintln!("[Taurium] Now showing: {}", id);
+    if let Some(previous) = previous.filter(|p| p != id) {
+        hibernate_if_switched_away(app, state, &previous);
+    }
     Ok(())
 }
 
+fn active_service_id(state: &WebviewState) -> Option<String> {
+    state
+        .active_id
+        .lock()
+        .ok()
+        .and_then(|active| active.clone())
+}
+
+/// Apply the "on switch away" hibernation policy to the service just hidden.
+/// Must not run on the main thread (closing the webview waits on it).
+fn hibernate_if_switched_away(app: &AppHandle, state: &WebviewState, id: &str) {
+    let on_switch_away = state.services.lock().is_ok_and(|services| {
+        services
+            .iter()
+            .any(|s| s.id == id && s.hibernation == HibernationPolicy::OnSwitchAway)
+    });
+    if !on_switch_away || app.get_webview(id).is_none() {
+        return;
+    }
+    eprintln!("[Taurium] Hibernating webview on switch away: {}", id);
+    if let Err(e) = close_service_webview(app, id, true) {
+        eprintln!("[Taurium] Failed to hibernate '{}': {}", id, e);
+    }
+}
+
 pub fn show_settings(app: &AppHandle, state: &WebviewState) -> Result<(), TauriumError> {
     eprintln!("[Taurium] Showing settings");
+    let previous = active_service_id(state);
     hide_all(app, state);
 
     let webview = app
@@ -872,6 +1687,14 @@ pub fn show_settings(app: &AppHandle, state: &WebviewState) -> Result<(), Tauriu
         .active_id
         .lock()
         .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))? = None;
+
+    // open_settings runs on the main thread, which closing a webview waits on.
+    if let Some(previous) = previous {
+        let app = app.clone();
+        std::thread::spawn(move || {
+            hibernate_if_switched_away(&app, &app.state::<WebviewState>(), &previous)
+        });
+    }
     Ok(())
 }
 
@@ -955,12 +1778,47 @@ fn cleanup_service_webview_state(
         .lock()
         .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))?
         .remove(id);
+    let mut hibernated = state
+        .hibernated
+        .lock()
+        .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))?;
+    if keep_badge {
+        hibernated.insert(id.to_string());
+    } else {
+        hibernated.remove(id);
+    }
+    drop(hibernated);
+    state
+        .page_urls
+        .lock()
+        .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))?
+        .remove(id);
+    state
+        .scroll_positions
+        .lock()
+        .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))?
+        .remove(id);
+    state
+        .pending_scroll
+        .lock()
+        .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))?
+        .remove(id);
     if !keep_badge {
         state
             .badge_counts
             .lock()
             .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))?
             .remove(id);
+        state
+            .notification_api_ids
+            .lock()
+            .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))?
+            .remove(id);
+        state
+            .notification_throttle
+            .lock()
+            .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))?
+            .forget_service(id);
     }
     state
         .last_activity
@@ -970,6 +1828,54 @@ fn cleanup_service_webview_state(
     Ok(())
 }
 
+/// Save the page a service is on before it is hibernated, so waking it up
+/// reopens it (see [`ensure_navigated`]). The webview's own URL follows
+/// in-page navigation (SPAs) that `on_page_load` doesn't see.
+fn remember_last_page(state: &WebviewState, id: &str, webview: &tauri::Webview) {
+    if !current_preferences(state).restore_last_page {
+        return;
+    }
+    let recorded = state
+        .page_urls
+        .lock()
+        .ok()
+        .and_then(|urls| urls.get(id).cloned());
+    let Some(url) = webview
+        .url()
+        .ok()
+        .map(String::from)
+        .filter(|url| is_meaningful_page_url(url))
+        .or(recorded)
+    else {
+        return;
+    };
+    let Some(home) = state
+        .services
+        .lock()
+        .ok()
+        .and_then(|services| services.iter().find(|s| s.id == id).map(|s| s.url.clone()))
+    else {
+        return;
+    };
+    let scroll_y = state
+        .scroll_positions
+        .lock()
+        .ok()
+        .and_then(|positions| positions.get(id).cloned())
+        .filter(|(scrolled_url, _)| *scrolled_url == url)
+        .map_or(0, |(_, y)| y);
+    let page = config::LastPage {
+        url,
+        home,
+        scroll_y,
+    };
+    if let Err(e) = config::update_state(&current_data_dir(state), |app_state| {
+        app_state.last_pages.insert(id.to_string(), page);
+    }) {
+        eprintln!("[Taurium] Failed to save the page of '{}': {}", id, e);
+    }
+}
+
 fn close_service_webview_inner(
     app: &AppHandle,
     state: &WebviewState,
@@ -977,6 +1883,9 @@ fn close_service_webview_inner(
     keep_badge: bool,
 ) -> Result<(), TauriumError> {
     if let Some(webview) = app.get_webview(id) {
+        if keep_badge {
+            remember_last_page(state, id, &webview);
+        }
         webview.hide().ok();
         webview.eval("window.location.replace('about:blank')").ok();
         webview.close()?;
@@ -1036,7 +1945,247 @@ pub fn recreate_service_webview(
         switch_to(app, state, &service.id)?;
     }
 
-    Ok(())
+    Ok(())
+}
+
+fn rename_throttled_service(
+    state: &WebviewState,
+    old_id: &str,
+    new_id: &str,
+) -> Result<(), TauriumError> {
+    state
+        .notification_throttle
+        .lock()
+        .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))?
+        .rename_service(old_id, new_id);
+    Ok(())
+}
+
+/// Renomme l'id d'un service sans perdre sa session. La webview est fermée
+/// d'abord (son processus garde `webview_data/<old>` ouvert), puis recréée
+/// sous le nouveau label au prochain switch — tout de suite si elle était active.
+pub fn rename_service(
+    app: &AppHandle,
+    state: &WebviewState,
+    old_id: &str,
+    new_id: &str,
+) -> Result<(), TauriumError> {
+    let mut services = state
+        .services
+        .lock()
+        .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))?
+        .clone();
+    let index = services
+        .iter()
+        .position(|s| s.id == old_id)
+        .ok_or_else(|| TauriumError::ServiceNotFound(old_id.to_string()))?;
+    if old_id == new_id {
+        return Ok(());
+    }
+    services[index].id = new_id.to_string();
+    // Only the renamed entry matters here; the rest was already accepted.
+    let problems: Vec<_> = config::validate_services(&services)
+        .into_iter()
+        .filter(|p| p.index == index)
+        .collect();
+    if !problems.is_empty() {
+        return Err(TauriumError::Validation(problems));
+    }
+
+    let was_active = state
+        .active_id
+        .lock()
+        .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))?
+        .as_deref()
+        == Some(old_id);
+    // Closing the webview drops everything kept under the old id: carry over
+    // what the service keeps under its new one.
+    let badge = state
+        .badge_counts
+        .lock()
+        .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))?
+        .get(old_id)
+        .copied();
+    let uses_notification_api = state
+        .notification_api_ids
+        .lock()
+        .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))?
+        .contains(old_id);
+    let last_activity = state
+        .last_activity
+        .lock()
+        .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))?
+        .get(old_id)
+        .copied();
+    rename_throttled_service(state, old_id, new_id)?;
+
+    if let Err(err) = close_service_webview(app, old_id, false) {
+        rename_throttled_service(state, new_id, old_id)?;
+        return Err(err);
+    }
+    // The list may have changed while the webview was closing.
+    let services = {
+        let mut services = state
+            .services
+            .lock()
+            .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))?
+            .clone();
+        if let Some(service) = services.iter_mut().find(|s| s.id == old_id) {
+            service.id = new_id.to_string();
+        }
+        services
+    };
+    if let Err(err) = config::rename_service_id(&current_data_dir(state), &services, old_id, new_id)
+    {
+        // Nothing moved: bring the old webview back so the user can retry.
+        rename_throttled_service(state, new_id, old_id)?;
+        if was_active {
+            let _ = switch_to(app, state, old_id);
+        }
+        return Err(err.into());
+    }
+
+    if let Some(service) = state
+        .services
+        .lock()
+        .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))?
+        .iter_mut()
+        .find(|s| s.id == old_id)
+    {
+        service.id = new_id.to_string();
+    }
+    if let Some(count) = badge {
+        state
+            .badge_counts
+            .lock()
+            .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))?
+            .insert(new_id.to_string(), count);
+    }
+    if uses_notification_api {
+        state
+            .notification_api_ids
+            .lock()
+            .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))?
+            .insert(new_id.to_string());
+    }
+    if let Some(instant) = last_activity {
+        state
+            .last_activity
+            .lock()
+            .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))?
+            .insert(new_id.to_string(), instant);
+    }
+    if let Ok(mut router) = app.state::<notifications::NotificationTracker>().0.lock() {
+        router.rename_service(old_id, new_id);
+    }
+    if was_active {
+        switch_to(app, state, new_id)?;
+    }
+
+    if let Some(sidebar) = app.get_webview("sidebar") {
+        sidebar
+            .eval("window.__reloadSidebar && window.__reloadSidebar()")
+            .ok();
+    }
+    eprintln!("[Taurium] Service id renamed: {old_id} -> {new_id}");
+    Ok(())
+}
+
+/// Remplace en place tout le jeu de services par celui d'un autre profil.
+/// Les webviews du profil courant sont supprimées (leurs processus gardent
+/// son `webview_data` ouvert) ; celles du nouveau profil sont créées à la
+/// demande par switch_to, comme au démarrage.
+pub fn switch_profile(
+    app: &AppHandle,
+    state: &WebviewState,
+    profile_dir: PathBuf,
+) -> Result<ServicesLoadInfo, TauriumError> {
+    // Load first: a broken services.json must leave the current profile intact.
+    let loaded = config::load_services(&profile_dir)?;
+    let preferences = config::load_preferences(&profile_dir);
+
+    let created = state
+        .created_ids
+        .lock()
+        .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))?
+        .clone();
+    for id in &created {
+        eprintln!("[Taurium] Removing webview: {}", id);
+        remove_service_webview(app, id)?;
+    }
+    // Hibernated services have no webview left to remove: drop what the old
+    // profile's services still have in memory.
+    state
+        .hibernated
+        .lock()
+        .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))?
+        .clear();
+    state
+        .page_urls
+        .lock()
+        .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))?
+        .clear();
+    state
+        .scroll_positions
+        .lock()
+        .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))?
+        .clear();
+    state
+        .pending_scroll
+        .lock()
+        .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))?
+        .clear();
+    state
+        .notification_api_ids
+        .lock()
+        .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))?
+        .clear();
+    *state
+        .notification_throttle
+        .lock()
+        .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))? = NotificationThrottle::default();
+    if let Ok(mut router) = app.state::<notifications::NotificationTracker>().0.lock() {
+        router.forget_all();
+    }
+
+    *state
+        .app_data_dir
+        .lock()
+        .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))? = profile_dir;
+    *state
+        .services
+        .lock()
+        .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))? = loaded.services;
+    *state
+        .active_id
+        .lock()
+        .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))? = None;
+    state
+        .badge_counts
+        .lock()
+        .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))?
+        .clear();
+    let prefs_json = serde_json::to_string(&preferences)?;
+    set_preferences(state, preferences)?;
+    update_taskbar_indicator(app, 0);
+
+    if let Some(sidebar) = app.get_webview("sidebar") {
+        sidebar
+            .eval(format!(
+                "window.__applyPreferences && window.__applyPreferences({prefs_json})"
+            ))
+            .ok();
+        sidebar
+            .eval("window.__reloadSidebar && window.__reloadSidebar()")
+            .ok();
+    }
+
+    Ok(ServicesLoadInfo {
+        filtered_url_count: loaded.filtered_url_count,
+        skipped_entries: loaded.skipped_entries,
+        load_error: None,
+        applied_migrations: loaded.applied_migrations,
+    })
 }
 
 /// Indique si une webview doit être recréée (user-agent modifié sur un service existant).
@@ -1044,6 +2193,12 @@ pub(crate) fn service_user_agent_changed(old: &Service, new: &Service) -> bool {
     old.id == new.id && old.user_agent != new.user_agent
 }
 
+/// Indique si le CSS/JS personnalisé d'un service existant a changé (le
+/// script d'initialisation est figé à la création de la webview).
+pub(crate) fn service_customization_changed(old: &Service, new: &Service) -> bool {
+    old.id == new.id && (old.custom_css != new.custom_css || old.custom_js != new.custom_js)
+}
+
 /// Apply service changes: handle reorder/delete/add instantly.
 pub fn apply_service_changes(
     app: &AppHandle,
@@ -1070,6 +2225,9 @@ pub fn apply_service_changes(
     for id in &to_remove {
         eprintln!("[Taurium] Removing webview: {}", id);
         remove_service_webview(app, id)?;
+        if let Ok(mut router) = app.state::<notifications::NotificationTracker>().0.lock() {
+            router.forget_service(id);
+        }
     }
 
     // Create newly added service webviews on-the-fly (best-effort: if creation
@@ -1085,7 +2243,8 @@ pub fn apply_service_changes(
         }
     }
 
-    // Recreate webviews when user-agent changed on existing services
+    // Recreate webviews when user-agent or customizations changed on existing
+    // services
     for service in &new_services {
         if !old_ids.contains(&service.id) {
             continue;
@@ -1099,6 +2258,12 @@ pub fn apply_service_changes(
                 service.id
             );
             recreate_service_webview(app, state, service)?;
+        } else if service_customization_changed(old, service) {
+            eprintln!(
+                "[Taurium] Custom CSS/JS changed for {}, recreating webview",
+                service.id
+            );
+            recreate_service_webview(app, state, service)?;
         }
     }
 
@@ -1136,6 +2301,78 @@ pub fn apply_service_changes(
     Ok(())
 }
 
+/// Wait before the first background load, so the active service loads alone.
+const PRELOAD_INITIAL_DELAY: Duration = Duration::from_secs(5);
+/// Pause between two background loads, to spread the CPU and network load.
+const PRELOAD_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Ids of the services to load at startup, in sidebar order. The active one
+/// is left out: the sidebar's initial switch loads it.
+pub(crate) fn services_to_preload(services: &[Service], active_id: Option<&str>) -> Vec<String> {
+    services
+        .iter()
+        .filter(|s| s.load_at_startup && active_id != Some(s.id.as_str()))
+        .map(|s| s.id.clone())
+        .collect()
+}
+
+/// Staggered background loader for the `load_at_startup` services: each one
+/// is created and navigated a few seconds apart, hidden, so its badges and
+/// notifications work before its first click. Nothing is shown: the active
+/// view keeps the focus. Blocks; run it on its own thread.
+pub fn preload_services(app: &AppHandle) {
+    std::thread::sleep(PRELOAD_INITIAL_DELAY);
+    let state = app.state::<WebviewState>();
+    // Read before taking the services lock: never hold two locks at once.
+    let active_id = active_service_id(&state);
+    let ids = match state.services.lock() {
+        Ok(services) => services_to_preload(&services, active_id.as_deref()),
+        Err(e) => {
+            eprintln!("[Taurium] Mutex poisoned: {}", e);
+            return;
+        }
+    };
+    for (i, id) in ids.iter().enumerate() {
+        if i > 0 {
+            std::thread::sleep(PRELOAD_INTERVAL);
+        }
+        if let Err(e) = preload_service(app, &state, id) {
+            eprintln!("[Taurium] Failed to load '{}' at startup: {}", id, e);
+        }
+    }
+}
+
+fn preload_service(app: &AppHandle, state: &WebviewState, id: &str) -> Result<(), TauriumError> {
+    // The user may have opened or removed the service in the meantime.
+    let already_loaded = state
+        .navigated
+        .lock()
+        .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))?
+        .contains(id);
+    let service = state
+        .services
+        .lock()
+        .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))?
+        .iter()
+        .find(|s| s.id == id)
+        .cloned();
+    let Some(service) = service.filter(|_| !already_loaded) else {
+        return Ok(());
+    };
+    eprintln!("[Taurium] Loading '{}' at startup", id);
+    if app.get_webview(id).is_none() {
+        create_service_webview(app, &service)?;
+    }
+    ensure_navigated(app, state, id);
+    // From now on, its hibernation policy applies as if it had been shown.
+    state
+        .last_activity
+        .lock()
+        .map_err(|e| TauriumError::MutexPoisoned(e.to_string()))?
+        .insert(id.to_string(), Instant::now());
+    Ok(())
+}
+
 /// Hibernate inactive webviews to save memory.
 ///
 /// Hibernation CLOSES the webview instead of navigating it to about:blank:
@@ -1145,14 +2382,10 @@ pub fn apply_service_changes(
 /// cost is the same as the old about:blank approach). The unread badge is
 /// kept so the sidebar still shows pending notifications.
 ///
-/// The idle delay comes from the `hibernation_minutes` preference
-/// (default 10); `0` disables hibernation entirely.
+/// The idle delay comes from each service's hibernation policy, by default
+/// the `hibernation_minutes` preference (default 10; `0` disables it).
 pub fn check_hibernation(app: &AppHandle, state: &WebviewState) {
-    let hibernation_minutes = load_preferences(&state.app_data_dir).hibernation_minutes;
-    if hibernation_minutes == 0 {
-        return;
-    }
-    let hibernation_secs = u64::from(hibernation_minutes) * 60;
+    let hibernation_minutes = current_preferences(state).hibernation_minutes;
 
     let active = match state.active_id.lock() {
         Ok(guard) => guard.clone(),
@@ -1163,21 +2396,20 @@ pub fn check_hibernation(app: &AppHandle, state: &WebviewState) {
     };
     let now = Instant::now();
 
-    // Services marked "keep alive" are exempt from hibernation: unloading them
-    // would stop their background JS, so they'd stop emitting title changes
-    // and Taurium would stop detecting new messages until the user manually
-    // switches back to them.
-    let keep_alive_ids: HashSet<String> = match state.services.lock() {
-        Ok(services) => services
-            .iter()
-            .filter(|s| s.keep_alive)
-            .map(|s| s.id.clone())
-            .collect(),
+    // Services with the "never" policy are exempt from hibernation: unloading
+    // them would stop their background JS, so they'd stop emitting title
+    // changes and Taurium would stop detecting new messages until the user
+    // manually switches back to them.
+    let thresholds = match state.services.lock() {
+        Ok(services) => hibernation_thresholds(&services, hibernation_minutes),
         Err(e) => {
             eprintln!("[Taurium] Mutex poisoned: {}", e);
-            HashSet::new()
+            return;
         }
     };
+    if thresholds.is_empty() {
+        return;
+    }
 
     // Collect candidates, then RELEASE the locks before closing: closing runs
     // on the main thread and re-locks this state, so holding the guards here
@@ -1197,13 +2429,13 @@ pub fn check_hibernation(app: &AppHandle, state: &WebviewState) {
                 return;
             }
         };
-        let navigated_ids = filter_hibernation_candidates(&navigated, &keep_alive_ids);
+        let navigated_ids: Vec<String> = navigated.iter().cloned().collect();
         select_webviews_to_hibernate(
             &navigated_ids,
             active.as_deref(),
             &last_activity,
             now,
-            hibernation_secs,
+            &thresholds,
         )
     };
 
@@ -1218,35 +2450,130 @@ pub fn check_hibernation(app: &AppHandle, state: &WebviewState) {
     }
 }
 
+/// Memory-pressure hibernation (Linux): while Taurium's process tree uses
+/// more than the `memory_limit_mb` preference (or the system runs low), close
+/// the least recently used service, one per check so the memory it frees is
+/// measured before going further. Unlike [`check_hibernation`], the idle time
+/// doesn't matter; only the "never" policy and the active service are spared.
+pub fn check_memory_pressure(app: &AppHandle, state: &WebviewState) {
+    let limit_mb = current_preferences(state).memory_limit_mb;
+    if limit_mb == 0 {
+        return;
+    }
+    let Some(usage) = memory_monitor::read_memory_usage() else {
+        return;
+    };
+    if !memory_monitor::is_under_pressure(&usage, limit_mb) {
+        return;
+    }
+
+    let active = active_service_id(state);
+    let exempt: HashSet<String> = match state.services.lock() {
+        Ok(services) => services
+            .iter()
+            .filter(|s| s.hibernation == HibernationPolicy::Never)
+            .map(|s| s.id.clone())
+            .collect(),
+        Err(e) => {
+            eprintln!("[Taurium] Mutex poisoned: {}", e);
+            return;
+        }
+    };
+    // Same as check_hibernation: release the locks before closing.
+    let to_hibernate = {
+        let (Ok(last_activity), Ok(navigated)) =
+            (state.last_activity.lock(), state.navigated.lock())
+        else {
+            eprintln!("[Taurium] Mutex poisoned while checking memory pressure");
+            return;
+        };
+        let navigated_ids: Vec<String> = navigated.iter().cloned().collect();
+        select_lru_to_hibernate(
+            &navigated_ids,
+            active.as_deref(),
+            &last_activity,
+            &exempt,
+            1,
+        )
+    };
+
+    for id in to_hibernate {
+        eprintln!(
+            "[Taurium] Memory pressure ({} MB used, {} MB available): hibernating {}",
+            usage.app_rss_kb / 1024,
+            usage.system.available_kb / 1024,
+            id
+        );
+        if let Err(e) = close_service_webview(app, &id, true) {
+            eprintln!("[Taurium] Failed to hibernate '{}': {}", id, e);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::{HashMap, HashSet};
     use std::time::{Duration, Instant};
 
     use super::{
-        classify_popup_url, cleanup_service_webview_state, compute_service_changes,
-        filter_hibernation_candidates, is_meaningful_page_url, notification_body_for_badge_change,
-        select_webviews_to_hibernate, service_user_agent_changed, window_location_replace_js,
-        PopupTarget, WebviewState,
+        apply_badge_count, apply_title_to_badges, classify_popup_url,
+        cleanup_service_webview_state, compute_service_changes, custom_injection_scripts,
+        direct_only_ids, hibernation_thresholds, is_meaningful_page_url,
+        notification_body_for_badge_change, scroll_restore_js, select_lru_to_hibernate,
+        select_webviews_to_hibernate, service_customization_changed, service_load_status,
+        service_user_agent_changed, services_to_preload, taskbar_total, window_location_replace_js,
+        BadgeCount, HeldNotification, NotificationThrottle, PopupTarget, ThrottleDecision,
+        WebviewState,
     };
-    use crate::config::{Service, ServicesLoadInfo};
+    use crate::config::{HibernationPolicy, Preferences, Service, ServicesLoadInfo};
     use tauri::Url;
 
+    fn direct(count: u32) -> BadgeCount {
+        BadgeCount {
+            direct: count,
+            indirect: 0,
+        }
+    }
+
+    fn test_service(id: &str) -> Service {
+        Service {
+            id: id.to_string(),
+            name: "Test".to_string(),
+            url: "https://example.com".to_string(),
+            icon: "x".to_string(),
+            user_agent: None,
+            zoom: None,
+            group: None,
+            notify: None,
+            hibernation: HibernationPolicy::Default,
+            notify_direct_only: false,
+            load_at_startup: false,
+            notify_schedule: Vec::new(),
+            custom_css: None,
+            custom_js: None,
+        }
+    }
+
     fn state_with_service(id: &str) -> WebviewState {
         let now = Instant::now();
         WebviewState {
             created_ids: std::sync::Mutex::new(vec![id.to_string()]),
             active_id: std::sync::Mutex::new(None),
-            app_data_dir: std::path::PathBuf::new(),
+            data_root: std::path::PathBuf::new(),
+            app_data_dir: std::sync::Mutex::new(std::path::PathBuf::new()),
             services: std::sync::Mutex::new(Vec::new()),
             navigated: std::sync::Mutex::new(HashSet::from([id.to_string()])),
+            hibernated: std::sync::Mutex::new(HashSet::new()),
+            page_urls: std::sync::Mutex::new(HashMap::new()),
+            scroll_positions: std::sync::Mutex::new(HashMap::new()),
+            pending_scroll: std::sync::Mutex::new(HashMap::new()),
             last_activity: std::sync::Mutex::new(HashMap::from([(id.to_string(), now)])),
-            badge_counts: std::sync::Mutex::new(HashMap::from([(id.to_string(), 3u32)])),
+            badge_counts: std::sync::Mutex::new(HashMap::from([(id.to_string(), direct(3))])),
             sidebar_width: std::sync::Mutex::new(super::SIDEBAR_WIDTH),
-            services_load_info: ServicesLoadInfo {
-                filtered_url_count: 0,
-                load_error: None,
-            },
+            services_load_info: ServicesLoadInfo::default(),
+            preferences: std::sync::Mutex::new(Preferences::default()),
+            notification_api_ids: std::sync::Mutex::new(HashSet::new()),
+            notification_throttle: std::sync::Mutex::new(NotificationThrottle::default()),
         }
     }
 
@@ -1257,9 +2584,13 @@ mod tests {
         assert!(state.created_ids.lock().unwrap().is_empty());
         assert!(state.navigated.lock().unwrap().is_empty());
         assert!(state.last_activity.lock().unwrap().is_empty());
+        assert!(state.hibernated.lock().unwrap().contains("svc"));
         // The unread badge must survive hibernation so the sidebar keeps
         // showing pending notifications for the closed webview.
-        assert_eq!(state.badge_counts.lock().unwrap().get("svc"), Some(&3));
+        assert_eq!(
+            state.badge_counts.lock().unwrap().get("svc"),
+            Some(&direct(3))
+        );
     }
 
     #[test]
@@ -1267,6 +2598,156 @@ mod tests {
         let state = state_with_service("svc");
         cleanup_service_webview_state(&state, "svc", false).unwrap();
         assert!(state.badge_counts.lock().unwrap().is_empty());
+        assert!(state.hibernated.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn services_to_preload_keeps_order_and_skips_active() {
+        let service = |id: &str, load_at_startup| Service {
+            load_at_startup,
+            ..test_service(id)
+        };
+        let services = [
+            service("slack", true),
+            service("mail", false),
+            service("chat", true),
+            service("wiki", true),
+        ];
+        assert_eq!(
+            services_to_preload(&services, Some("chat")),
+            vec!["slack".to_string(), "wiki".to_string()]
+        );
+        assert_eq!(
+            services_to_preload(&services, None),
+            vec!["slack".to_string(), "chat".to_string(), "wiki".to_string()]
+        );
+        assert!(services_to_preload(&services[1..2], None).is_empty());
+    }
+
+    #[test]
+    fn load_status_from_webview_bookkeeping() {
+        let created = vec!["mail".to_string(), "blank".to_string()];
+        let navigated = HashSet::from(["mail".to_string()]);
+        let hibernated = HashSet::from(["chat".to_string()]);
+        let status = |id| service_load_status(id, &created, &navigated, &hibernated);
+        assert_eq!(status("mail"), "navigated");
+        assert_eq!(status("chat"), "hibernated");
+        assert_eq!(status("blank"), "not_loaded");
+        assert_eq!(status("unseen"), "not_loaded");
+    }
+
+    #[test]
+    fn title_change_reads_cached_preferences_not_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = state_with_service("svc");
+        *state.app_data_dir.lock().unwrap() = dir.path().to_path_buf();
+        // preferences.json disagrees with the cache, then disappears entirely:
+        // only the cached value may decide.
+        crate::config::save_preferences(
+            dir.path(),
+            &Preferences {
+                notifications_enabled: false,
+                ..Preferences::default()
+            },
+        )
+        .unwrap();
+        drop(dir);
+
+        let update = apply_title_to_badges(&state, "svc", "(5) Inbox").unwrap();
+        assert!(update.notify_allowed);
+        assert_eq!(
+            (update.prev_count, update.count, update.total),
+            (direct(3), direct(5), 5)
+        );
+        assert_eq!(update.badges_json, r#"{"svc":{"direct":5,"indirect":0}}"#);
+
+        state.preferences.lock().unwrap().notifications_enabled = false;
+        let update = apply_title_to_badges(&state, "svc", "(6) Inbox").unwrap();
+        assert!(!update.notify_allowed);
+    }
+
+    #[test]
+    fn reported_badge_counts_share_the_title_badge_map() {
+        let state = state_with_service("svc");
+        state
+            .badge_counts
+            .lock()
+            .unwrap()
+            .insert("chat".to_string(), direct(2));
+
+        let update = apply_badge_count(&state, "svc", direct(4)).unwrap();
+        assert_eq!(
+            (update.prev_count, update.count, update.total),
+            (direct(3), direct(4), 6)
+        );
+
+        let update = apply_badge_count(&state, "svc", BadgeCount::default()).unwrap();
+        assert_eq!(update.total, 2);
+        assert_eq!(update.badges_json, r#"{"chat":{"direct":2,"indirect":0}}"#);
+    }
+
+    #[test]
+    fn indirect_counts_notify_only_unless_direct_only() {
+        let state = state_with_service("svc");
+        let mut service = test_service("svc");
+        state.services.lock().unwrap().push(service.clone());
+
+        // Channel activity: badged, counted and notified by default.
+        let activity = BadgeCount {
+            direct: 3,
+            indirect: 7,
+        };
+        let update = apply_badge_count(&state, "svc", activity).unwrap();
+        assert_eq!(update.total, 10);
+        assert_eq!(update.notified_counts(), (10, 3));
+
+        service.notify_direct_only = true;
+        *state.services.lock().unwrap() = vec![service];
+        let more_activity = BadgeCount {
+            direct: 3,
+            indirect: 9,
+        };
+        let update = apply_badge_count(&state, "svc", more_activity).unwrap();
+        assert_eq!(update.total, 3);
+        let (count, prev) = update.notified_counts();
+        assert_eq!((count, prev), (3, 3));
+        assert_eq!(
+            notification_body_for_badge_change("Chat", count, prev, true),
+            None
+        );
+
+        let mention = BadgeCount {
+            direct: 4,
+            indirect: 9,
+        };
+        let update = apply_badge_count(&state, "svc", mention).unwrap();
+        assert_eq!(update.notified_counts(), (4, 3));
+    }
+
+    #[test]
+    fn taskbar_total_leaves_out_indirect_counts_of_direct_only_services() {
+        let mut chat = test_service("chat");
+        chat.notify_direct_only = true;
+        let mail = test_service("mail");
+        let badges = HashMap::from([
+            (
+                "chat".to_string(),
+                BadgeCount {
+                    direct: 2,
+                    indirect: 40,
+                },
+            ),
+            (
+                "mail".to_string(),
+                BadgeCount {
+                    direct: 1,
+                    indirect: 5,
+                },
+            ),
+        ]);
+        let direct_only = direct_only_ids(&[chat, mail]);
+        assert_eq!(direct_only, HashSet::from(["chat".to_string()]));
+        assert_eq!(taskbar_total(&badges, &direct_only), 2 + 1 + 5);
     }
 
     #[test]
@@ -1357,7 +2838,12 @@ mod tests {
             zoom: None,
             group: None,
             notify: None,
-            keep_alive: false,
+            hibernation: HibernationPolicy::Default,
+            custom_css: None,
+            custom_js: None,
+            notify_direct_only: false,
+            load_at_startup: false,
+            notify_schedule: Vec::new(),
         }
     }
 
@@ -1506,27 +2992,220 @@ mod tests {
     }
 
     #[test]
-    fn filter_hibernation_candidates_excludes_keep_alive() {
-        let navigated: HashSet<String> = ["a", "b", "c"].iter().map(|s| s.to_string()).collect();
-        let keep_alive: HashSet<String> = ["b"].iter().map(|s| s.to_string()).collect();
-        let mut candidates = filter_hibernation_candidates(&navigated, &keep_alive);
-        candidates.sort();
-        assert_eq!(candidates, vec!["a".to_string(), "c".to_string()]);
+    fn throttle_merges_increases_inside_the_interval() {
+        let mut throttle = NotificationThrottle::default();
+        let interval = Duration::from_secs(10);
+        let t0 = Instant::now();
+        let at = |secs: u64| t0 + Duration::from_secs(secs);
+
+        assert_eq!(
+            throttle.record("slack", 1, interval, t0),
+            ThrottleDecision::Show(1)
+        );
+        // Two more increases within 10s: one deferral, then merged.
+        assert_eq!(
+            throttle.record("slack", 2, interval, at(3)),
+            ThrottleDecision::Defer(Duration::from_secs(7))
+        );
+        assert_eq!(
+            throttle.record("slack", 1, interval, at(5)),
+            ThrottleDecision::Merged
+        );
+        // Other services have their own window.
+        assert_eq!(
+            throttle.record("mail", 1, interval, at(5)),
+            ThrottleDecision::Show(1)
+        );
+        assert_eq!(throttle.flush("slack", at(10)), Some(3));
+        // The flush started a new interval.
+        assert_eq!(
+            throttle.record("slack", 1, interval, at(15)),
+            ThrottleDecision::Defer(Duration::from_secs(5))
+        );
+        assert_eq!(throttle.flush("slack", at(20)), Some(1));
+        assert_eq!(
+            throttle.record("slack", 4, interval, at(31)),
+            ThrottleDecision::Show(4)
+        );
+    }
+
+    #[test]
+    fn throttle_edge_cases() {
+        let mut throttle = NotificationThrottle::default();
+        let interval = Duration::from_secs(10);
+        let t0 = Instant::now();
+        let at = |secs: u64| t0 + Duration::from_secs(secs);
+
+        // A late timer: the next increase takes the held-back messages along.
+        throttle.record("slack", 1, interval, t0);
+        throttle.record("slack", 2, interval, at(1));
+        assert_eq!(
+            throttle.record("slack", 1, interval, at(12)),
+            ThrottleDecision::Show(3)
+        );
+        assert_eq!(throttle.flush("slack", at(12)), None);
+
+        // Read meanwhile: nothing to show at the end of the interval.
+        throttle.record("slack", 1, interval, at(13));
+        throttle.discard_pending("slack");
+        assert_eq!(throttle.flush("slack", at(22)), None);
+
+        // A zero interval never throttles; unknown or forgotten ids flush nothing.
+        assert_eq!(
+            throttle.record("mail", 1, Duration::ZERO, t0),
+            ThrottleDecision::Show(1)
+        );
+        assert_eq!(
+            throttle.record("mail", 1, Duration::ZERO, t0),
+            ThrottleDecision::Show(1)
+        );
+        throttle.forget_service("slack");
+        assert_eq!(throttle.flush("slack", at(30)), None);
+        assert_eq!(throttle.flush("nope", at(30)), None);
+
+        // Renamed mid-deferral: the old timer finds nothing, the held-back
+        // messages go out with the next notification under the new id.
+        throttle.record("chat", 1, interval, at(40));
+        throttle.record("chat", 2, interval, at(41));
+        throttle.rename_service("chat", "team");
+        assert_eq!(throttle.flush("chat", at(50)), None);
+        assert_eq!(
+            throttle.record("team", 1, interval, at(51)),
+            ThrottleDecision::Show(3)
+        );
+    }
+
+    #[test]
+    fn throttle_keeps_the_latest_web_notification() {
+        let mut throttle = NotificationThrottle::default();
+        let interval = Duration::from_secs(10);
+        let t0 = Instant::now();
+        let at = |secs: u64| t0 + Duration::from_secs(secs);
+        let held = |body: &str| HeldNotification {
+            title: "Slack : Alice".to_string(),
+            body: body.to_string(),
+            icon: None,
+        };
+
+        // Shown right away: nothing is kept.
+        throttle.record("slack", 1, interval, t0);
+        throttle.hold("slack", held("first"));
+        assert_eq!(throttle.take_held("slack"), None);
+
+        throttle.record("slack", 1, interval, at(2));
+        throttle.hold("slack", held("second"));
+        throttle.record("slack", 1, interval, at(4));
+        throttle.hold("slack", held("third"));
+        assert_eq!(throttle.flush("slack", at(10)), Some(2));
+        assert_eq!(throttle.take_held("slack"), Some(held("third")));
+        assert_eq!(throttle.take_held("slack"), None);
+
+        // Read meanwhile, or shown along with a later one: dropped.
+        throttle.record("slack", 1, interval, at(12));
+        throttle.hold("slack", held("read"));
+        throttle.discard_pending("slack");
+        assert_eq!(throttle.take_held("slack"), None);
+        throttle.record("slack", 1, interval, at(22));
+        throttle.record("slack", 1, interval, at(23));
+        throttle.hold("slack", held("late"));
+        assert_eq!(
+            throttle.record("slack", 1, interval, at(33)),
+            ThrottleDecision::Show(2)
+        );
+        assert_eq!(throttle.take_held("slack"), None);
+    }
+
+    /// The same idle threshold for every id.
+    fn every(ids: &[&str], secs: u64) -> HashMap<String, u64> {
+        ids.iter().map(|id| (id.to_string(), secs)).collect()
+    }
+
+    #[test]
+    fn hibernation_thresholds_follow_each_policy() {
+        let service = |id: &str, hibernation| Service {
+            hibernation,
+            ..test_service(id)
+        };
+        let services = [
+            service("default", HibernationPolicy::Default),
+            service("never", HibernationPolicy::Never),
+            service("after", HibernationPolicy::After { minutes: 2 }),
+            service("heavy", HibernationPolicy::OnSwitchAway),
+        ];
+        assert_eq!(
+            hibernation_thresholds(&services, 10),
+            HashMap::from([
+                ("default".to_string(), 600),
+                ("after".to_string(), 120),
+                ("heavy".to_string(), 0),
+            ])
+        );
+        // A global `0` only disables the services that follow it.
+        assert_eq!(
+            hibernation_thresholds(&services, 0),
+            HashMap::from([("after".to_string(), 120), ("heavy".to_string(), 0)])
+        );
     }
 
     #[test]
-    fn filter_hibernation_candidates_empty_keep_alive_is_noop() {
-        let navigated: HashSet<String> = ["a", "b"].iter().map(|s| s.to_string()).collect();
-        let mut candidates = filter_hibernation_candidates(&navigated, &HashSet::new());
-        candidates.sort();
-        assert_eq!(candidates, vec!["a".to_string(), "b".to_string()]);
+    fn select_webviews_to_hibernate_uses_per_id_thresholds() {
+        let now = Instant::now();
+        let idle = |secs| now - Duration::from_secs(secs);
+        let last_activity = HashMap::from([
+            ("short".to_string(), idle(200)),
+            ("long".to_string(), idle(200)),
+            ("never".to_string(), idle(100_000)),
+            ("heavy".to_string(), idle(1)),
+        ]);
+        let thresholds = HashMap::from([
+            ("short".to_string(), 120),
+            ("long".to_string(), 600),
+            ("heavy".to_string(), 0),
+        ]);
+        let mut selected = select_webviews_to_hibernate(
+            &[
+                "short".to_string(),
+                "long".to_string(),
+                "never".to_string(),
+                "heavy".to_string(),
+            ],
+            None,
+            &last_activity,
+            now,
+            &thresholds,
+        );
+        selected.sort();
+        assert_eq!(selected, vec!["heavy".to_string(), "short".to_string()]);
     }
 
     #[test]
-    fn filter_hibernation_candidates_all_keep_alive_is_empty() {
-        let navigated: HashSet<String> = ["a", "b"].iter().map(|s| s.to_string()).collect();
-        let candidates = filter_hibernation_candidates(&navigated, &navigated);
-        assert!(candidates.is_empty());
+    fn select_lru_to_hibernate_orders_by_last_activity() {
+        let now = Instant::now();
+        let ago = |secs| now - Duration::from_secs(secs);
+        // Idle times don't matter: even a service shown a second ago goes.
+        let last_activity = HashMap::from([
+            ("recent".to_string(), ago(1)),
+            ("old".to_string(), ago(3000)),
+            ("older".to_string(), ago(6000)),
+            ("pinned".to_string(), ago(9000)),
+            ("active".to_string(), ago(9999)),
+        ]);
+        let navigated: Vec<String> = ["recent", "old", "older", "pinned", "active", "unseen"]
+            .iter()
+            .map(|id| id.to_string())
+            .collect();
+        let exempt = HashSet::from(["pinned".to_string()]);
+
+        let selected =
+            select_lru_to_hibernate(&navigated, Some("active"), &last_activity, &exempt, 10);
+        assert_eq!(selected, vec!["unseen", "older", "old", "recent"]);
+
+        let selected =
+            select_lru_to_hibernate(&navigated, Some("active"), &last_activity, &exempt, 2);
+        assert_eq!(selected, vec!["unseen", "older"]);
+
+        let selected = select_lru_to_hibernate(&[], None, &last_activity, &exempt, 1);
+        assert!(selected.is_empty());
     }
 
     #[test]
@@ -1538,7 +3217,7 @@ mod tests {
             Some("active"),
             &last_activity,
             now,
-            600,
+            &every(&["active"], 600),
         );
         assert!(selected.is_empty());
     }
@@ -1547,8 +3226,13 @@ mod tests {
     fn select_webviews_to_hibernate_selects_idle_navigated() {
         let now = Instant::now();
         let last_activity = HashMap::from([("idle".to_string(), now - Duration::from_secs(601))]);
-        let selected =
-            select_webviews_to_hibernate(&["idle".to_string()], None, &last_activity, now, 600);
+        let selected = select_webviews_to_hibernate(
+            &["idle".to_string()],
+            None,
+            &last_activity,
+            now,
+            &every(&["idle"], 600),
+        );
         assert_eq!(selected, vec!["idle".to_string()]);
     }
 
@@ -1562,7 +3246,7 @@ mod tests {
             None,
             &last_activity,
             now,
-            600,
+            &every(&["borderline"], 600),
         );
         assert!(selected.is_empty());
     }
@@ -1570,8 +3254,13 @@ mod tests {
     #[test]
     fn select_webviews_to_hibernate_skips_without_activity_entry() {
         let now = Instant::now();
-        let selected =
-            select_webviews_to_hibernate(&["orphan".to_string()], None, &HashMap::new(), now, 600);
+        let selected = select_webviews_to_hibernate(
+            &["orphan".to_string()],
+            None,
+            &HashMap::new(),
+            now,
+            &every(&["orphan"], 600),
+        );
         assert!(selected.is_empty());
     }
 
@@ -1579,8 +3268,13 @@ mod tests {
     fn select_webviews_to_hibernate_skips_not_yet_idle() {
         let now = Instant::now();
         let last_activity = HashMap::from([("recent".to_string(), now - Duration::from_secs(30))]);
-        let selected =
-            select_webviews_to_hibernate(&["recent".to_string()], None, &last_activity, now, 600);
+        let selected = select_webviews_to_hibernate(
+            &["recent".to_string()],
+            None,
+            &last_activity,
+            now,
+            &every(&["recent"], 600),
+        );
         assert!(selected.is_empty());
     }
 
@@ -1603,7 +3297,7 @@ mod tests {
             Some("active"),
             &last_activity,
             now,
-            600,
+            &every(&["idle-a", "active", "idle-b", "recent"], 600),
         );
         assert_eq!(selected, vec!["idle-a".to_string(), "idle-b".to_string()]);
     }
@@ -1612,10 +3306,18 @@ mod tests {
     fn select_webviews_to_hibernate_ignores_non_navigated() {
         let now = Instant::now();
         let last_activity = HashMap::from([("hidden".to_string(), now - Duration::from_secs(900))]);
-        let selected = select_webviews_to_hibernate(&[], None, &last_activity, now, 600);
+        let selected =
+            select_webviews_to_hibernate(&[], None, &last_activity, now, &every(&["hidden"], 600));
         assert!(selected.is_empty());
     }
 
+    #[test]
+    fn scroll_restore_js_targets_the_saved_position() {
+        let js = scroll_restore_js(640);
+        assert!(js.contains("window.scrollTo(0,640)"));
+        assert!(js.contains("window.scrollY-640"));
+    }
+
     #[test]
     fn test_url_escaping_in_window_location_replace_js() {
         let urls = [
@@ -1649,7 +3351,12 @@ mod tests {
             zoom: None,
             group: None,
             notify: None,
-            keep_alive: false,
+            hibernation: HibernationPolicy::Default,
+            custom_css: None,
+            custom_js: None,
+            notify_direct_only: false,
+            load_at_startup: false,
+            notify_schedule: Vec::new(),
         };
         let with_ua = Service {
             user_agent: Some("Custom".to_string()),
@@ -1659,4 +3366,26 @@ mod tests {
         assert!(service_user_agent_changed(&base, &with_ua));
         assert!(service_user_agent_changed(&with_ua, &base));
     }
+
+    #[test]
+    fn custom_injection_changes_trigger_recreate() {
+        let base = test_service("svc");
+        let styled = Service {
+            custom_css: Some("body { color: red }".to_string()),
+            ..base.clone()
+        };
+        assert!(!service_customization_changed(&base, &base));
+        assert!(service_customization_changed(&base, &styled));
+        assert!(!service_user_agent_changed(&base, &styled));
+
+        assert!(custom_injection_scripts(None, Some("  ")).is_empty());
+        let scripts = custom_injection_scripts(Some("a::after { content: \"</style>\" }"), None);
+        assert_eq!(scripts.len(), 1);
+        assert!(scripts[0].contains(r#"var css = "a::after { content: \"</style>\" }";"#));
+        assert!(!scripts[0].contains("runJs"));
+        // A user script that doesn't even parse leaves the style script intact.
+        let scripts = custom_injection_scripts(Some("p { margin: 0 }"), Some("if ("));
+        assert_eq!(scripts.len(), 2);
+        assert!(!scripts[0].contains("if (") && scripts[1].contains("if ("));
+    }
 }
@@ -0,0 +1,726 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsStr;
+use std::fmt;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+use crate::config::{
+    self, load_state, parse_services_document, sanitize_services, save_services, save_state,
+    ParsedServices, Preferences, Service,
+};
+
+/// `format` tag written to every workspace manifest.
+pub const WORKSPACE_FORMAT: &str = "taurium-workspace";
+/// Archive layout version; bump when the entry layout below changes.
+pub const WORKSPACE_FORMAT_VERSION: u32 = 1;
+
+const MANIFEST_ENTRY: &str = "manifest.json";
+const SERVICES_ENTRY: &str = "services.json";
+const PREFERENCES_ENTRY: &str = "preferences.json";
+const STATE_ENTRY: &str = "state.json";
+/// Per-service session data lives under `webview_data/<service id>/…`, the
+/// same layout as in the app data dir.
+const WEBVIEW_DATA_DIR: &str = "webview_data";
+
+/// First entry of every workspace archive; describes what it contains.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkspaceManifest {
+    pub format: String,
+    pub format_version: u32,
+    /// Taurium version that wrote the archive (informational).
+    pub app_version: String,
+    /// Unix time in milliseconds.
+    pub created_ms: u64,
+    pub service_ids: Vec<String>,
+    /// Whether `webview_data/<id>` session directories were bundled.
+    pub includes_webview_data: bool,
+}
+
+#[derive(Debug)]
+pub enum WorkspaceError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    Archive(zip::result::ZipError),
+    /// Not a Taurium workspace, or written by a newer, incompatible version.
+    InvalidArchive(String),
+    /// The archived services fail [`config::validate_services`], like a save
+    /// from the settings page would; indexes are positions in the archive.
+    Validation(Vec<config::ServiceFieldError>),
+}
+
+impl fmt::Display for WorkspaceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorkspaceError::Io(e) => write!(f, "{e}"),
+            WorkspaceError::Json(e) => write!(f, "{e}"),
+            WorkspaceError::Archive(e) => write!(f, "{e}"),
+            WorkspaceError::InvalidArchive(msg) => write!(f, "invalid workspace archive: {msg}"),
+            WorkspaceError::Validation(problems) => write!(
+                f,
+                "invalid services in workspace archive: {}",
+                problems
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for WorkspaceError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WorkspaceError::Io(e) => Some(e),
+            WorkspaceError::Json(e) => Some(e),
+            WorkspaceError::Archive(e) => Some(e),
+            WorkspaceError::InvalidArchive(_) | WorkspaceError::Validation(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for WorkspaceError {
+    fn from(value: io::Error) -> Self {
+        WorkspaceError::Io(value)
+    }
+}
+
+impl From<serde_json::Error> for WorkspaceError {
+    fn from(value: serde_json::Error) -> Self {
+        WorkspaceError::Json(value)
+    }
+}
+
+impl From<zip::result::ZipError> for WorkspaceError {
+    fn from(value: zip::result::ZipError) -> Self {
+        WorkspaceError::Archive(value)
+    }
+}
+
+impl From<config::ConfigError> for WorkspaceError {
+    fn from(value: config::ConfigError) -> Self {
+        match value {
+            config::ConfigError::Io(e) => WorkspaceError::Io(e),
+            config::ConfigError::Json(e) => WorkspaceError::Json(e),
+            err @ config::ConfigError::NewerServicesFile { .. } => {
+                WorkspaceError::Io(io::Error::new(io::ErrorKind::InvalidData, err.to_string()))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportReport {
+    pub path: PathBuf,
+    pub service_count: usize,
+    /// Session files that couldn't be read (typically locked by a running
+    /// webview) and were left out of the archive.
+    pub skipped_files: Vec<String>,
+}
+
+/// Bundle services.json (with the services' custom CSS / JS), preferences.json,
+/// state.json and (optionally) every service's `webview_data/<id>` directory
+/// into a zip archive at `dest`.
+/// The archive is written next to `dest` first and renamed into place, so a
+/// failed export never leaves a truncated file behind.
+pub fn export_workspace(
+    app_data_dir: &Path,
+    dest: &Path,
+    include_webview_data: bool,
+    app_version: &str,
+    now_ms: u64,
+) -> Result<ExportReport, WorkspaceError> {
+    let services_content = fs::read_to_string(config::get_services_path(app_data_dir))?;
+    let services = match parse_services_document(&services_content)? {
+        ParsedServices::Current { services, .. } => services,
+        ParsedServices::TooNew(found) => {
+            return Err(WorkspaceError::InvalidArchive(format!(
+                "services.json uses unsupported schema version {found}"
+            )))
+        }
+    };
+
+    let manifest = WorkspaceManifest {
+        format: WORKSPACE_FORMAT.to_string(),
+        format_version: WORKSPACE_FORMAT_VERSION,
+        app_version: app_version.to_string(),
+        created_ms: now_ms,
+        service_ids: services.iter().map(|s| s.id.clone()).collect(),
+        includes_webview_data: include_webview_data,
+    };
+
+    let tmp = {
+        let mut name = dest
+            .file_name()
+            .map(|n| n.to_os_string())
+            .unwrap_or_default();
+        name.push(".tmp");
+        dest.with_file_name(name)
+    };
+    let result = write_archive(
+        app_data_dir,
+        &tmp,
+        &manifest,
+        &services,
+        &config::embed_service_customizations(app_data_dir, services_content.as_bytes()),
+    );
+    let skipped_files = match result {
+        Ok(skipped) => skipped,
+        Err(e) => {
+            let _ = fs::remove_file(&tmp);
+            return Err(e);
+        }
+    };
+    fs::rename(&tmp, dest)?;
+
+    Ok(ExportReport {
+        path: dest.to_path_buf(),
+        service_count: services.len(),
+        skipped_files,
+    })
+}
+
+fn write_archive(
+    app_data_dir: &Path,
+    path: &Path,
+    manifest: &WorkspaceManifest,
+    services: &[Service],
+    services_content: &[u8],
+) -> Result<Vec<String>, WorkspaceError> {
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    let mut zip = zip::ZipWriter::new(fs::File::create(path)?);
+
+    zip.start_file(MANIFEST_ENTRY, options)?;
+    zip.write_all(serde_json::to_string_pretty(manifest)?.as_bytes())?;
+    zip.start_file(SERVICES_ENTRY, options)?;
+    zip.write_all(services_content)?;
+    for entry in [PREFERENCES_ENTRY, STATE_ENTRY] {
+        match fs::read(app_data_dir.join(entry)) {
+            Ok(content) => {
+                zip.start_file(entry, options)?;
+                zip.write_all(&content)?;
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    let mut skipped = Vec::new();
+    if manifest.includes_webview_data {
+        for service in services {
+            if !is_plain_component(&service.id) {
+                continue;
+            }
+            let root = app_data_dir.join(WEBVIEW_DATA_DIR).join(&service.id);
+            let prefix = format!("{WEBVIEW_DATA_DIR}/{}", service.id);
+            add_dir_to_archive(&mut zip, &root, &prefix, options, &mut skipped)?;
+        }
+    }
+
+    zip.finish()?.sync_all()?;
+    Ok(skipped)
+}
+
+/// Recursively add `dir` under `prefix`. Unreadable files are recorded in
+/// `skipped` instead of failing the whole export.
+fn add_dir_to_archive(
+    zip: &mut zip::ZipWriter<fs::File>,
+    dir: &Path,
+    prefix: &str,
+    options: zip::write::SimpleFileOptions,
+    skipped: &mut Vec<String>,
+) -> Result<(), WorkspaceError> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e.into()),
+    };
+    for entry in entries {
+        let entry = entry?;
+        let Some(name) = entry.file_name().to_str().map(str::to_string) else {
+            skipped.push(entry.path().display().to_string());
+            continue;
+        };
+        let entry_name = format!("{prefix}/{name}");
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            add_dir_to_archive(zip, &entry.path(), &entry_name, options, skipped)?;
+        } else if file_type.is_file() {
+            match fs::read(entry.path()) {
+                Ok(content) => {
+                    zip.start_file(entry_name, options)?;
+                    zip.write_all(&content)?;
+                }
+                Err(_) => skipped.push(entry_name),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `true` for a single, normal path component (no separators, `..`, roots).
+fn is_plain_component(name: &str) -> bool {
+    let mut components = Path::new(name).components();
+    matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    ) && !name.contains(['/', '\\'])
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ImportOptions {
+    /// Restore the bundled `webview_data/<id>` sessions of imported services.
+    #[serde(default)]
+    pub include_webview_data: bool,
+    /// Replace the current preferences.json with the archived one.
+    #[serde(default)]
+    pub replace_preferences: bool,
+}
+
+/// An imported service whose id was already taken by a different service.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RenamedService {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportReport {
+    /// Final ids of the services appended to the list.
+    pub added: Vec<String>,
+    pub renamed: Vec<RenamedService>,
+    /// Imported ids already present with the same URL (kept as they are).
+    pub skipped: Vec<String>,
+    /// Imported entries dropped because their URL is invalid or not http(s).
+    pub filtered_url_count: usize,
+    pub preferences_imported: bool,
+    /// Number of services whose session directory was restored.
+    pub webview_data_imported: usize,
+}
+
+/// Merge `imported` into `existing`. An imported service whose id is free is
+/// added as is; one whose id matches an existing service with the same URL is
+/// the same service and is skipped; any other collision, including with one
+/// of the [`config::RESERVED_SERVICE_IDS`], gets a fresh `<id>-<n>` id. Returns the services to append and the old → new id map
+/// (identity for unrenamed ones).
+pub(crate) fn resolve_import_ids(
+    existing: &[Service],
+    imported: Vec<Service>,
+    report: &mut ImportReport,
+) -> (Vec<Service>, HashMap<String, String>) {
+    let mut taken: HashSet<String> = existing
+        .iter()
+        .map(|s| s.id.clone())
+        .chain(config::RESERVED_SERVICE_IDS.map(String::from))
+        .collect();
+    let mut to_add = Vec::new();
+    let mut id_map = HashMap::new();
+    for mut service in imported {
+        if let Some(current) = existing.iter().find(|s| s.id == service.id) {
+            if current.url == service.url {
+                report.skipped.push(service.id);
+                continue;
+            }
+        }
+        if taken.contains(&service.id) {
+            let mut n = 2;
+            let mut candidate = format!("{}-{n}", service.id);
+            while taken.contains(&candidate) {
+                n += 1;
+                candidate = format!("{}-{n}", service.id);
+            }
+            report.renamed.push(RenamedService {
+                from: service.id.clone(),
+                to: candidate.clone(),
+            });
+            id_map.insert(service.id.clone(), candidate.clone());
+            service.id = candidate;
+        } else {
+            id_map.insert(service.id.clone(), service.id.clone());
+        }
+        taken.insert(service.id.clone());
+        to_add.push(service);
+    }
+    (to_add, id_map)
+}
+
+fn read_entry<R: Read + io::Seek>(
+    archive: &mut zip::ZipArchive<R>,
+    name: &str,
+) -> Result<Option<String>, WorkspaceError> {
+    let mut file = match archive.by_name(name) {
+        Ok(file) => file,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let mut content = String::new();
+    file.read_to_string(&mut content)?;
+    Ok(Some(content))
+}
+
+/// Merge the workspace archive at `archive_path` into the app data dir.
+/// `existing` is the current service list; the merged list is run through
+/// `sanitize_services`, saved (the previous services.json goes to the
+/// history first) and returned so the caller can apply it to the webviews.
+pub fn import_workspace(
+    app_data_dir: &Path,
+    archive_path: &Path,
+    existing: &[Service],
+    options: &ImportOptions,
+) -> Result<(ImportReport, Vec<Service>), WorkspaceError> {
+    let mut archive = zip::ZipArchive::new(fs::File::open(archive_path)?)?;
+
+    let manifest: WorkspaceManifest = match read_entry(&mut archive, MANIFEST_ENTRY)? {
+        Some(content) => serde_json::from_str(&content)?,
+        None => {
+            return Err(WorkspaceError::InvalidArchive(
+                "missing manifest.json".to_string(),
+            ))
+        }
+    };
+    if manifest.format != WORKSPACE_FORMAT {
+        return Err(WorkspaceError::InvalidArchive(format!(
+            "unknown format {:?}",
+            manifest.format
+        )));
+    }
+    if manifest.format_version > WORKSPACE_FORMAT_VERSION {
+        return Err(WorkspaceError::InvalidArchive(format!(
+            "format version {} is newer than supported ({WORKSPACE_FORMAT_VERSION})",
+            manifest.format_version
+        )));
+    }
+
+    let services_content = read_entry(&mut archive, SERVICES_ENTRY)?
+        .ok_or_else(|| WorkspaceError::InvalidArchive("missing services.json".to_string()))?;
+    let imported = match parse_services_document(&services_content)? {
+        ParsedServices::Current { services, .. } => services,
+        ParsedServices::TooNew(found) => {
+            return Err(WorkspaceError::InvalidArchive(format!(
+                "services.json uses unsupported schema version {found}"
+            )))
+        }
+    };
+
+    // Same rules as save_services_cmd: nothing is written unless every
+    // archived service would pass them. Reserved ids are renamed below, like
+    // ids already in use.
+    let problems: Vec<_> = config::validate_services(&imported)
+        .into_iter()
+        .filter(|p| p.code != "reserved_id")
+        .collect();
+    if !problems.is_empty() {
+        return Err(WorkspaceError::Validation(problems));
+    }
+
+    let mut report = ImportReport::default();
+    let (imported, filtered_url_count) = sanitize_services(imported);
+    report.filtered_url_count = filtered_url_count;
+    let (to_add, id_map) = resolve_import_ids(existing, imported, &mut report);
+    report.added = to_add.iter().map(|s| s.id.clone()).collect();
+
+    let mut merged = existing.to_vec();
+    merged.extend(to_add);
+    let (mut merged, _) = sanitize_services(merged);
+
+    // Parse everything before writing anything, so a bad entry can't leave
+    // a half-imported workspace behind.
+    let prefs: Option<Preferences> = match read_entry(&mut archive, PREFERENCES_ENTRY)? {
+        Some(content) if options.replace_preferences => Some(serde_json::from_str(&content)?),
+        _ => None,
+    };
+    let archived_state: config::AppState = read_entry(&mut archive, STATE_ENTRY)?
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+
+    if let Err(e) = config::snapshot_services(app_data_dir) {
+        // Not fatal: the import itself is still wanted.
+        eprintln!("[Taurium] Failed to snapshot services.json before import: {e}");
+    }
+    save_services(app_data_dir, &merged)?;
+
+    if let Some(prefs) = prefs {
+        config::save_preferences(app_data_dir, &prefs)?;
+        report.preferences_imported = true;
+    }
+
+    // Only adopt the archived active service when none is set locally.
+    let mut state = load_state(app_data_dir);
+    if state.last_active_service.is_none() {
+        if let Some(id) = archived_state
+            .last_active_service
+            .and_then(|id| id_map.get(&id))
+        {
+            state.last_active_service = Some(id.clone());
+            save_state(app_data_dir, &state)?;
+        }
+    }
+
+    if options.include_webview_data && manifest.includes_webview_data {
+        report.webview_data_imported =
+            extract_webview_data(&mut archive, app_data_dir, &id_map, &report.added)?;
+        // The archived session may carry custom.css / custom.js.
+        config::load_service_customizations(app_data_dir, &mut merged);
+    }
+
+    Ok((report, merged))
+}
+
+/// Extract `webview_data/<old id>/…` entries of the added services into
+/// `webview_data/<new id>/…`. Returns how many services got data.
+fn extract_webview_data<R: Read + io::Seek>(
+    archive: &mut zip::ZipArchive<R>,
+    app_data_dir: &Path,
+    id_map: &HashMap<String, String>,
+    added: &[String],
+) -> Result<usize, WorkspaceError> {
+    let mut restored: HashSet<String> = HashSet::new();
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        // `enclosed_name` rejects absolute paths and `..` (zip-slip).
+        let Some(path) = file.enclosed_name() else {
+            continue;
+        };
+        let mut components = path.components();
+        if components.next() != Some(Component::Normal(OsStr::new(WEBVIEW_DATA_DIR))) {
+            continue;
+        }
+        let Some(Component::Normal(old_id)) = components.next() else {
+            continue;
+        };
+        let Some(new_id) = old_id.to_str().and_then(|id| id_map.get(id)) else {
+            continue;
+        };
+        if !added.contains(new_id) || !is_plain_component(new_id) {
+            continue;
+        }
+        let rest = components.as_path();
+        if rest.as_os_str().is_empty() {
+            continue;
+        }
+        let target = app_data_dir.join(WEBVIEW_DATA_DIR).join(new_id).join(rest);
+        if file.is_dir() {
+            fs::create_dir_all(&target)?;
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut out = fs::File::create(&target)?;
+        io::copy(&mut file, &mut out)?;
+        restored.insert(new_id.clone());
+    }
+    Ok(restored.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn service(id: &str, url: &str) -> Service {
+        serde_json::from_value(serde_json::json!({
+            "id": id, "name": id, "url": url, "icon": "x"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn resolve_import_ids_handles_collisions() {
+        let existing = vec![
+            service("slack", "https://app.slack.com"),
+            service("mail", "https://mail.example.com"),
+            service("mail-2", "https://other.example.com"),
+        ];
+        let imported = vec![
+            service("slack", "https://app.slack.com"),
+            service("mail", "https://mail.team.example.com"),
+            service("new", "https://new.example.com"),
+            service("settings", "https://settings.example.com"),
+        ];
+        let mut report = ImportReport::default();
+        let (to_add, id_map) = resolve_import_ids(&existing, imported, &mut report);
+
+        assert_eq!(report.skipped, vec!["slack"]);
+        assert_eq!(
+            report.renamed,
+            vec![
+                RenamedService {
+                    from: "mail".to_string(),
+                    to: "mail-3".to_string()
+                },
+                RenamedService {
+                    from: "settings".to_string(),
+                    to: "settings-2".to_string()
+                }
+            ]
+        );
+        let ids: Vec<&str> = to_add.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, vec!["mail-3", "new", "settings-2"]);
+        assert_eq!(id_map.get("mail").map(String::as_str), Some("mail-3"));
+        assert_eq!(id_map.get("new").map(String::as_str), Some("new"));
+        assert!(!id_map.contains_key("slack"));
+    }
+
+    #[test]
+    fn export_then_import_round_trips_into_another_workspace() {
+        let source = tempdir().unwrap();
+        save_services(
+            source.path(),
+            &[
+                service("slack", "https://app.slack.com"),
+                service("mail", "https://mail.example.com"),
+            ],
+        )
+        .unwrap();
+        config::save_preferences(
+            source.path(),
+            &Preferences {
+                icon_size: 56,
+                ..Preferences::default()
+            },
+        )
+        .unwrap();
+        save_state(
+            source.path(),
+            &config::AppState {
+                last_active_service: Some("mail".to_string()),
+                ..config::AppState::default()
+            },
+        )
+        .unwrap();
+        let cookies = source.path().join("webview_data/mail/Default");
+        fs::create_dir_all(&cookies).unwrap();
+        fs::write(cookies.join("Cookies"), b"session").unwrap();
+
+        let archive = source.path().join("workspace.zip");
+        let exported = export_workspace(source.path(), &archive, true, "0.0.0", 42).unwrap();
+        assert_eq!(exported.service_count, 2);
+        assert!(exported.skipped_files.is_empty());
+        assert!(!source.path().join("workspace.zip.tmp").exists());
+
+        // The destination already has a different "mail" service.
+        let dest = tempdir().unwrap();
+        let existing = vec![service("mail", "https://other-mail.example.com")];
+        save_services(dest.path(), &existing).unwrap();
+        let options = ImportOptions {
+            include_webview_data: true,
+            replace_preferences: true,
+        };
+        let (report, merged) =
+            import_workspace(dest.path(), &archive, &existing, &options).unwrap();
+
+        assert_eq!(report.added, vec!["slack", "mail-2"]);
+        assert!(report.preferences_imported);
+        assert_eq!(report.webview_data_imported, 1);
+        let ids: Vec<&str> = merged.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, vec!["mail", "slack", "mail-2"]);
+        assert_eq!(
+            config::load_services(dest.path()).unwrap().services.len(),
+            3
+        );
+        assert_eq!(config::load_preferences(dest.path()).icon_size, 56);
+        assert_eq!(
+            load_state(dest.path()).last_active_service.as_deref(),
+            Some("mail-2")
+        );
+        assert_eq!(
+            fs::read(dest.path().join("webview_data/mail-2/Default/Cookies")).unwrap(),
+            b"session"
+        );
+        // The renamed service's data never lands in the existing one's dir.
+        assert!(!dest.path().join("webview_data/mail").exists());
+    }
+
+    #[test]
+    fn exports_without_sessions_keep_customizations() {
+        let source = tempdir().unwrap();
+        let mut styled = service("mail", "https://mail.example.com");
+        styled.custom_css = Some(".ad { display: none }".to_string());
+        save_services(source.path(), &[styled]).unwrap();
+        let archive = source.path().join("workspace.zip");
+        export_workspace(source.path(), &archive, false, "0.0.0", 42).unwrap();
+
+        let dest = tempdir().unwrap();
+        let options = ImportOptions {
+            include_webview_data: false,
+            replace_preferences: false,
+        };
+        let (_, merged) = import_workspace(dest.path(), &archive, &[], &options).unwrap();
+        assert_eq!(
+            merged[0].custom_css.as_deref(),
+            Some(".ad { display: none }")
+        );
+        let loaded = config::load_services(dest.path()).unwrap().services;
+        assert_eq!(
+            loaded[0].custom_css.as_deref(),
+            Some(".ad { display: none }")
+        );
+    }
+
+    #[test]
+    fn import_rejects_foreign_archives() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("other.zip");
+        let mut zip = zip::ZipWriter::new(fs::File::create(&path).unwrap());
+        zip.start_file("manifest.json", zip::write::SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(br#"{"format":"something-else","format_version":1,"app_version":"","created_ms":0,"service_ids":[],"includes_webview_data":false}"#)
+            .unwrap();
+        zip.finish().unwrap();
+
+        let result = import_workspace(dir.path(), &path, &[], &ImportOptions::default());
+        assert!(matches!(result, Err(WorkspaceError::InvalidArchive(_))));
+        assert!(!config::get_services_path(dir.path()).exists());
+    }
+
+    #[test]
+    fn import_validates_services_before_writing() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("bad.zip");
+        let manifest = WorkspaceManifest {
+            format: WORKSPACE_FORMAT.to_string(),
+            format_version: WORKSPACE_FORMAT_VERSION,
+            app_version: String::new(),
+            created_ms: 0,
+            service_ids: vec!["mail".to_string(), "evil".to_string()],
+            includes_webview_data: false,
+        };
+        let services = serde_json::json!({
+            "schema_version": config::SERVICES_SCHEMA_VERSION,
+            "services": [
+                {"id": "mail", "name": "Mail", "url": "https://mail.example.com", "icon": "x"},
+                {"id": "evil", "name": " ", "url": "javascript:alert(1)", "icon": "x"},
+            ]
+        });
+        let mut zip = zip::ZipWriter::new(fs::File::create(&path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        zip.start_file(MANIFEST_ENTRY, options).unwrap();
+        zip.write_all(serde_json::to_string(&manifest).unwrap().as_bytes())
+            .unwrap();
+        zip.start_file(SERVICES_ENTRY, options).unwrap();
+        zip.write_all(services.to_string().as_bytes()).unwrap();
+        zip.finish().unwrap();
+
+        let result = import_workspace(dir.path(), &path, &[], &ImportOptions::default());
+        let Err(WorkspaceError::Validation(problems)) = result else {
+            panic!("expected a validation error, got {result:?}");
+        };
+        let fields: Vec<(usize, &str)> = problems.iter().map(|p| (p.index, p.field)).collect();
+        assert_eq!(fields, vec![(1, "name"), (1, "url")]);
+        assert!(!config::get_services_path(dir.path()).exists());
+    }
+
+    #[test]
+    fn plain_component_rejects_paths() {
+        assert!(is_plain_component("abc-123"));
+        assert!(!is_plain_component("../x"));
+        assert!(!is_plain_component("a/b"));
+        assert!(!is_plain_component("a\\b"));
+        assert!(!is_plain_component(".."));
+        assert!(!is_plain_component(""));
+    }
+}
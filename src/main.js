@@ -315,6 +315,17 @@ window.__applyPreferences = function(prefs) {
   applyPreferences(prefs);
 };
 
+// Called from Rust after a hand-edited services.json was reloaded with warnings
+window.__showServicesLoadInfo = function(info) {
+  showServicesLoadInfo(info);
+};
+
+// Called from Rust when a hand-edited config file can't be reloaded; the
+// previous configuration stays active.
+window.__showConfigError = function(file, error) {
+  showToast(file + " n\u2019a pas \u00e9t\u00e9 recharg\u00e9 : " + error, { durationMs: 10000 });
+};
+
 // Expand / collapse the sidebar. The Rust side reflows the native service
 // webviews to start after the sidebar and persists the pinned state.
 async function setSidebarExpanded(expanded) {
@@ -484,6 +495,13 @@ window.__reloadSidebar = async function() {
   }
 };
 
+// A notification was clicked and Rust switched to its service (called via eval)
+window.__setActiveService = function(id) {
+  activeId = id;
+  settingsOpen = false;
+  updateActiveState();
+};
+
 // Badge update callback (called from Rust via eval)
 window.__updateBadges = function(badges) {
   document.querySelectorAll(".service-icon").forEach((btn) => {
@@ -495,11 +513,19 @@ window.__updateBadges = function(badges) {
     const existing = wrap.querySelector(".badge");
     if (existing) existing.remove();
 
+    // { direct, indirect }: direct counts are shown as a number, indirect
+    // activity alone (unread channels) as a dot.
     const count = badges[id];
-    if (count && count > 0) {
+    if (!count) return;
+    if (count.direct > 0) {
       const badge = document.createElement("span");
       badge.className = "badge";
-      badge.textContent = count > 99 ? "99+" : count;
+      badge.textContent = count.direct > 99 ? "99+" : count.direct;
+      wrap.appendChild(badge);
+    } else if (count.indirect > 0) {
+      const badge = document.createElement("span");
+      badge.className = "badge badge--dot";
+      badge.title = count.indirect + " non lu(s)";
       wrap.appendChild(badge);
     }
   });
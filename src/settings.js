@@ -16,12 +16,9 @@ import { getVersion } from "@tauri-apps/api/app";
 
 let pendingUpdate = null;
 
-function nanoid(size = 10) {
-  const alphabet = 'ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789_-';
-  let id = '';
-  const bytes = crypto.getRandomValues(new Uint8Array(size));
-  for (let i = 0; i < size; i++) id += alphabet[bytes[i] & 63];
-  return id;
+// Ids are generated by the backend (slug of the name, unique in the list).
+async function newServiceId(name) {
+  return await getInvoke()("generate_service_id", { name });
 }
 
 // Populate the group autocomplete from groups already used by other services
@@ -89,6 +86,42 @@ function isEmojiIcon(s) {
   return /\p{Extended_Pictographic}/u.test(s);
 }
 
+// Fill the preferences form (at startup and after a profile switch).
+function showPreferences(prefs) {
+  loadedPrefs = prefs;
+  selectedTheme = ["auto", "dark", "light"].includes(prefs.theme) ? prefs.theme : "dark";
+  selectedAccent = ACCENT_PRESETS.includes(prefs.accent_color) ? prefs.accent_color : "blue";
+  applySnowPrefs();
+  renderThemeControls();
+  document.getElementById("pref-icon-size").value = prefs.icon_size;
+  document.getElementById("pref-icon-size-val").textContent = prefs.icon_size + "px";
+  document.getElementById("pref-notifications").checked = prefs.notifications_enabled;
+  const intervalSelect = document.getElementById("pref-notification-interval");
+  intervalSelect.value = String(prefs.notification_interval_seconds ?? 10);
+  if (intervalSelect.value !== String(prefs.notification_interval_seconds ?? 10)) {
+    intervalSelect.value = "10";
+  }
+  const quiet = prefs.quiet_hours ?? {};
+  document.getElementById("pref-quiet-enabled").checked = !!quiet.enabled;
+  document.getElementById("pref-quiet-start").value = quiet.start ?? "19:00";
+  document.getElementById("pref-quiet-end").value = quiet.end ?? "08:00";
+  document.getElementById("pref-quiet-weekends").checked = !!quiet.weekends;
+  const hibernationSelect = document.getElementById("pref-hibernation");
+  hibernationSelect.value = String(prefs.hibernation_minutes ?? 10);
+  // A hand-edited preferences.json can hold a value with no matching
+  // option; fall back to the default so the select isn't left blank.
+  if (hibernationSelect.value !== String(prefs.hibernation_minutes ?? 10)) {
+    hibernationSelect.value = "10";
+  }
+  document.getElementById("pref-restore-last-page").checked = prefs.restore_last_page ?? true;
+  document.getElementById("pref-notification-focus").checked = !!prefs.notification_focus_switches;
+  const memoryLimitSelect = document.getElementById("pref-memory-limit");
+  memoryLimitSelect.value = String(prefs.memory_limit_mb ?? 0);
+  if (memoryLimitSelect.value !== String(prefs.memory_limit_mb ?? 0)) {
+    memoryLimitSelect.value = "0";
+  }
+}
+
 async function init() {
   let invoke = getInvoke();
   if (!invoke) {
@@ -109,23 +142,12 @@ async function init() {
     showServicesLoadInfo(loadInfo);
 
     // Load preferences
-    const prefs = await invoke("get_preferences");
-    loadedPrefs = prefs;
-    selectedTheme = ["auto", "dark", "light"].includes(prefs.theme) ? prefs.theme : "dark";
-    selectedAccent = ACCENT_PRESETS.includes(prefs.accent_color) ? prefs.accent_color : "blue";
-    applySnowPrefs();
+    showPreferences(await invoke("get_preferences"));
     initThemeControls();
-    renderThemeControls();
-    document.getElementById("pref-icon-size").value = prefs.icon_size;
-    document.getElementById("pref-icon-size-val").textContent = prefs.icon_size + "px";
-    document.getElementById("pref-notifications").checked = prefs.notifications_enabled;
-    const hibernationSelect = document.getElementById("pref-hibernation");
-    hibernationSelect.value = String(prefs.hibernation_minutes ?? 10);
-    // A hand-edited preferences.json can hold a value with no matching
-    // option; fall back to the default so the select isn't left blank.
-    if (hibernationSelect.value !== String(prefs.hibernation_minutes ?? 10)) {
-      hibernationSelect.value = "10";
-    }
+    refreshSnoozeState();
+    loadNotificationHistory();
+
+    await loadProfiles();
   } catch (err) {
     showToast("Impossible de charger les réglages : " + formatInvokeError(err), { durationMs: 10000 });
     console.error("Settings init error:", err);
@@ -140,11 +162,25 @@ async function init() {
   document.getElementById("catalog-btn").addEventListener("click", showCatalog);
   document.getElementById("catalog-close").addEventListener("click", hideCatalog);
   document.getElementById("catalog-search").addEventListener("input", renderCatalogList);
+  document.getElementById("history-btn").addEventListener("click", showHistory);
+  document.getElementById("export-workspace-btn").addEventListener("click", exportWorkspace);
+  document.getElementById("import-workspace-btn").addEventListener("click", importWorkspace);
+  document.getElementById("profile-switch-btn").addEventListener("click", switchProfile);
+  document.getElementById("profile-delete-btn").addEventListener("click", deleteProfile);
+  document.getElementById("profile-create-btn").addEventListener("click", createProfile);
+  document.getElementById("history-close").addEventListener("click", hideHistory);
+  document.getElementById("history-restore").addEventListener("click", restoreSelectedSnapshot);
   document.getElementById("save-btn").addEventListener("click", saveForm);
+  document.getElementById("schedule-add-btn").addEventListener("click", () =>
+    addScheduleRuleRow({ days: [0, 1, 2, 3, 4], start: "09:00", end: "18:00", level: "all" }),
+  );
   document.getElementById("cancel-btn").addEventListener("click", hideForm);
   document.getElementById("confirm-yes").addEventListener("click", confirmDelete);
   document.getElementById("confirm-no").addEventListener("click", cancelDelete);
   document.getElementById("save-prefs-btn").addEventListener("click", savePreferences);
+  document.getElementById("activity-clear-btn").addEventListener("click", clearNotificationHistory);
+  document.getElementById("snooze-btn").addEventListener("click", () => snoozeNotifications(60));
+  document.getElementById("unsnooze-btn").addEventListener("click", unsnoozeNotifications);
   document.getElementById("trust-cert-btn").addEventListener("click", handleTrustCertClick);
   document.getElementById("cert-trust-yes").addEventListener("click", confirmTrustCert);
   document.getElementById("cert-trust-no").addEventListener("click", cancelTrustCert);
@@ -315,6 +351,12 @@ function showDeleteConfirm(index) {
 }
 
 function confirmDelete() {
+  if (deleteProfileId) {
+    const id = deleteProfileId;
+    cancelDelete();
+    removeProfile(id);
+    return;
+  }
   if (deleteIndex >= 0) {
     services.splice(deleteIndex, 1);
     renderServices();
@@ -325,6 +367,7 @@ function confirmDelete() {
 
 function cancelDelete() {
   deleteIndex = -1;
+  deleteProfileId = null;
   document.getElementById("confirm-dialog").classList.add("hidden");
 }
 
@@ -491,8 +534,14 @@ function showAddForm() {
   document.getElementById("input-zoom").value = "1";
   document.getElementById("input-zoom-val").textContent = "1.0×";
   document.getElementById("input-notify").value = "all";
-  document.getElementById("input-keep-alive").checked = false;
+  showScheduleRules([]);
+  showHibernationPolicy(null);
+  document.getElementById("input-notify-direct-only").checked = false;
+  document.getElementById("input-load-at-startup").checked = false;
+  document.getElementById("input-custom-css").value = "";
+  document.getElementById("input-custom-js").value = "";
   document.getElementById("input-icon-file").value = "";
+  document.getElementById("field-id").classList.add("hidden");
   refreshIconPreview();
   clearErrors();
   document.getElementById("edit-form").classList.remove("hidden");
@@ -503,6 +552,8 @@ function showEditForm(index) {
   const s = services[index];
   document.getElementById("form-title").textContent = "Modifier le service";
   document.getElementById("input-name").value = s.name;
+  document.getElementById("input-id").value = s.id;
+  document.getElementById("field-id").classList.remove("hidden");
   document.getElementById("input-url").value = s.url;
   document.getElementById("input-group").value = s.group ?? "";
   refreshGroupSuggestions();
@@ -512,7 +563,12 @@ function showEditForm(index) {
   document.getElementById("input-zoom-val").textContent = Number(z).toFixed(1) + "×";
   const notify = s.notify === "badge" || s.notify === "off" ? s.notify : "all";
   document.getElementById("input-notify").value = notify;
-  document.getElementById("input-keep-alive").checked = !!s.keep_alive;
+  showScheduleRules(s.notify_schedule);
+  showHibernationPolicy(s.hibernation);
+  document.getElementById("input-notify-direct-only").checked = !!s.notify_direct_only;
+  document.getElementById("input-load-at-startup").checked = !!s.load_at_startup;
+  document.getElementById("input-custom-css").value = s.custom_css ?? "";
+  document.getElementById("input-custom-js").value = s.custom_js ?? "";
 
   // Icône : image importée, Lucide, ou emoji hérité
   if (s.icon.startsWith("data:image")) {
@@ -534,6 +590,103 @@ function showEditForm(index) {
   document.getElementById("edit-form").classList.remove("hidden");
 }
 
+// --- Notification schedule (per-service time windows, see notify_schedule.rs) ---
+const SCHEDULE_DAYS = ["L", "M", "M", "J", "V", "S", "D"];
+const SCHEDULE_LEVELS = [
+  ["all", "Tout"],
+  ["badge", "Badge seul"],
+  ["off", "Muet"],
+];
+
+function addScheduleRuleRow(rule) {
+  const row = document.createElement("div");
+  row.className = "schedule-rule";
+
+  const level = document.createElement("select");
+  level.className = "input schedule-level";
+  for (const [value, label] of SCHEDULE_LEVELS) {
+    const opt = document.createElement("option");
+    opt.value = value;
+    opt.textContent = label;
+    level.appendChild(opt);
+  }
+  level.value = rule.level;
+
+  const start = document.createElement("input");
+  start.type = "time";
+  start.className = "input schedule-start";
+  start.value = rule.start;
+  const end = document.createElement("input");
+  end.type = "time";
+  end.className = "input schedule-end";
+  end.value = rule.end;
+
+  // No day selected means every day, as in services.json.
+  const days = document.createElement("div");
+  days.className = "seg schedule-days";
+  SCHEDULE_DAYS.forEach((label, day) => {
+    const btn = document.createElement("button");
+    btn.type = "button";
+    btn.textContent = label;
+    btn.dataset.day = String(day);
+    btn.classList.toggle("on", rule.days.includes(day));
+    btn.addEventListener("click", () => btn.classList.toggle("on"));
+    days.appendChild(btn);
+  });
+
+  const remove = document.createElement("button");
+  remove.type = "button";
+  remove.className = "btn btn--ghost btn--sm";
+  remove.textContent = "Retirer";
+  remove.addEventListener("click", () => row.remove());
+
+  row.append(level, start, "→", end, days, remove);
+  document.getElementById("schedule-rules").appendChild(row);
+}
+
+function showScheduleRules(rules) {
+  document.getElementById("schedule-rules").innerHTML = "";
+  (rules ?? []).forEach(addScheduleRuleRow);
+}
+
+function readScheduleRules() {
+  return Array.from(document.querySelectorAll("#schedule-rules .schedule-rule")).map((row) => ({
+    days: Array.from(row.querySelectorAll(".schedule-days button.on")).map((b) => Number(b.dataset.day)),
+    start: row.querySelector(".schedule-start").value || "00:00",
+    end: row.querySelector(".schedule-end").value || "00:00",
+    level: row.querySelector(".schedule-level").value,
+  }));
+}
+
+// --- Hibernation policy (select value: "default", "never", "after:N", "on_switch_away") ---
+function showHibernationPolicy(policy) {
+  const select = document.getElementById("input-hibernation");
+  // A hand-edited delay without a preset gets its own option.
+  select.querySelectorAll("option[data-custom]").forEach((opt) => opt.remove());
+  let value = "default";
+  if (policy?.mode === "never" || policy?.mode === "on_switch_away") {
+    value = policy.mode;
+  } else if (policy?.mode === "after" && Number.isInteger(policy.minutes)) {
+    value = "after:" + policy.minutes;
+    if (!select.querySelector(`option[value="${value}"]`)) {
+      const opt = document.createElement("option");
+      opt.value = value;
+      opt.dataset.custom = "true";
+      opt.textContent = `Après ${policy.minutes} min d'inactivité`;
+      select.appendChild(opt);
+    }
+  }
+  select.value = value;
+}
+
+function readHibernationPolicy() {
+  const value = document.getElementById("input-hibernation").value;
+  if (value.startsWith("after:")) {
+    return { mode: "after", minutes: parseInt(value.slice("after:".length), 10) };
+  }
+  return { mode: value };
+}
+
 function hideForm() {
   document.getElementById("edit-form").classList.add("hidden");
   clearErrors();
@@ -571,7 +724,14 @@ async function saveForm() {
   // Notification level: "all" is the default and stored as null to keep the file clean.
   const notifyRaw = document.getElementById("input-notify").value;
   const notify = notifyRaw === "badge" || notifyRaw === "off" ? notifyRaw : null;
-  const keep_alive = document.getElementById("input-keep-alive").checked;
+  const notify_schedule = readScheduleRules();
+  const hibernation = readHibernationPolicy();
+  const notify_direct_only = document.getElementById("input-notify-direct-only").checked;
+  const load_at_startup = document.getElementById("input-load-at-startup").checked;
+  const customCssRaw = document.getElementById("input-custom-css").value;
+  const custom_css = customCssRaw.trim().length > 0 ? customCssRaw : null;
+  const customJsRaw = document.getElementById("input-custom-js").value;
+  const custom_js = customJsRaw.trim().length > 0 ? customJsRaw : null;
   const emojiIcon = document.getElementById("input-icon").value.trim();
 
   let valid = true;
@@ -608,7 +768,17 @@ async function saveForm() {
 
   if (!valid) return;
 
-  const id = editingIndex === -1 ? nanoid(10) : services[editingIndex].id;
+  let id;
+  try {
+    id = editingIndex === -1 ? await newServiceId(name) : await renameIfNeeded(editingIndex);
+  } catch (err) {
+    if (err && err.type === "Validation" && Array.isArray(err.problems) && err.problems.length > 0) {
+      showError("input-id", err.problems[0].message);
+    } else {
+      showToast("Impossible de renommer le service : " + formatInvokeError(err));
+    }
+    return;
+  }
 
   // Icône retenue : image importée > Lucide > emoji > globe par défaut
   let icon;
@@ -623,7 +793,22 @@ async function saveForm() {
   }
 
   if (editingIndex === -1) {
-    services.push({ id, name, url, icon, user_agent, zoom, group, notify, keep_alive });
+    services.push({
+      id,
+      name,
+      url,
+      icon,
+      user_agent,
+      zoom,
+      group,
+      notify,
+      notify_schedule,
+      hibernation,
+      notify_direct_only,
+      load_at_startup,
+      custom_css,
+      custom_js,
+    });
   } else {
     services[editingIndex] = {
       ...services[editingIndex],
@@ -635,7 +820,12 @@ async function saveForm() {
       zoom,
       group,
       notify,
-      keep_alive,
+      notify_schedule,
+      hibernation,
+      notify_direct_only,
+      load_at_startup,
+      custom_css,
+      custom_js,
     };
   }
 
@@ -644,16 +834,50 @@ async function saveForm() {
   await persistServices();
 }
 
+// Flag the rows rejected by save_services_cmd (problems carry the list index);
+// the next renderServices() clears the marks.
+function markInvalidServices(problems) {
+  const items = document.querySelectorAll("#service-list .service-item");
+  problems.forEach((problem) => {
+    const item = items[problem.index];
+    if (!item) return;
+    item.classList.add("invalid");
+    item.title = item.title ? item.title + "\n" + problem.message : problem.message;
+  });
+}
+
+// Rename the edited service's id first (moves its session directory), so
+// the save that follows only carries the other field changes.
+async function renameIfNeeded(index) {
+  const oldId = services[index].id;
+  const newId = document.getElementById("input-id").value.trim();
+  if (!newId || newId === oldId) return oldId;
+  await getInvoke()("rename_service_id", { oldId, newId });
+  services[index].id = newId;
+  return newId;
+}
+
 async function persistServices() {
   const invoke = getInvoke();
   if (!invoke) return;
   try {
     await invoke("save_services_cmd", { services });
     const applyResult = await invoke("apply_services");
-    if (applyResult && applyResult.filtered_url_count > 0) {
-      showServicesLoadInfo(applyResult);
-    }
+    showServicesLoadInfo(applyResult);
   } catch (err) {
+    if (err && err.type === "NewerConfig") {
+      // The file itself can't be written, whatever the entries: shown like a
+      // services.json load error.
+      showServicesLoadInfo({
+        load_error: "Services non enregistrés : services.json provient d’une version plus récente de Taurium.",
+      });
+      return;
+    }
+    if (err && err.type === "Validation" && Array.isArray(err.problems)) {
+      markInvalidServices(err.problems);
+      showToast("Services non enregistrés : corrigez les entrées signalées en rouge.");
+      return;
+    }
     showToast("Impossible d’enregistrer les services : " + formatInvokeError(err));
     console.error("Save services error:", err);
   }
@@ -717,15 +941,24 @@ function renderCatalogList() {
 }
 
 async function addFromCatalog(recipe) {
+  let id;
+  try {
+    id = await newServiceId(recipe.name);
+  } catch (err) {
+    showToast("Impossible d’ajouter le service : " + formatInvokeError(err));
+    return;
+  }
   const service = {
-    id: nanoid(10),
+    id,
     name: recipe.name,
     url: recipe.url,
     icon: recipe.icon,
     user_agent: recipe.user_agent ?? null,
     zoom: null,
     notify: null,
-    keep_alive: false,
+    hibernation: { mode: "default" },
+    notify_direct_only: false,
+    load_at_startup: false,
   };
   services.push(service);
   hideCatalog();
@@ -733,6 +966,265 @@ async function addFromCatalog(recipe) {
   await persistServices();
 }
 
+// --- services.json history ---
+let selectedSnapshotId = null;
+
+const DIFF_FIELD_LABELS = {
+  name: "nom",
+  url: "URL",
+  icon: "icône",
+  user_agent: "user agent",
+  zoom: "zoom",
+  group: "groupe",
+  notify: "notifications",
+  notify_schedule: "horaires de notification",
+  hibernation: "mise en veille",
+  notify_direct_only: "messages directs seulement",
+  load_at_startup: "chargement au démarrage",
+};
+
+async function showHistory() {
+  const invoke = getInvoke();
+  if (!invoke) return;
+  selectedSnapshotId = null;
+  document.getElementById("history-detail").classList.add("hidden");
+  const list = document.getElementById("history-list");
+  list.innerHTML = "";
+  try {
+    const snapshots = await invoke("list_services_snapshots");
+    document.getElementById("history-empty").classList.toggle("hidden", snapshots.length > 0);
+    snapshots.forEach((snap) => {
+      const item = document.createElement("button");
+      item.type = "button";
+      item.className = "catalog-item";
+      item.dataset.id = snap.id;
+      const count = snap.service_count == null
+        ? "illisible"
+        : snap.service_count + (snap.service_count === 1 ? " service" : " services");
+      item.innerHTML = `
+        <div class="info">
+          <div class="name">${escapeHtml(new Date(snap.timestamp_ms).toLocaleString("fr-FR"))}</div>
+          <div class="url">${escapeHtml(count)}</div>
+        </div>
+      `;
+      item.addEventListener("click", () => selectSnapshot(snap.id));
+      list.appendChild(item);
+    });
+    document.getElementById("history-dialog").classList.remove("hidden");
+  } catch (err) {
+    showToast("Impossible de charger l’historique : " + formatInvokeError(err));
+    console.error("List snapshots error:", err);
+  }
+}
+
+function hideHistory() {
+  selectedSnapshotId = null;
+  document.getElementById("history-dialog").classList.add("hidden");
+}
+
+async function selectSnapshot(id) {
+  const invoke = getInvoke();
+  if (!invoke) return;
+  document.querySelectorAll("#history-list .catalog-item").forEach((item) => {
+    item.classList.toggle("selected", item.dataset.id === id);
+  });
+  try {
+    const diff = await invoke("diff_services_snapshot", { id });
+    selectedSnapshotId = id;
+    const lines = [];
+    diff.added.forEach((s) => lines.push(`Rétabli : ${s.name}`));
+    diff.removed.forEach((s) => lines.push(`Supprimé : ${s.name}`));
+    diff.changed.forEach((s) => {
+      const fields = s.fields.map((f) => DIFF_FIELD_LABELS[f] || f).join(", ");
+      lines.push(`Modifié : ${s.name} (${fields})`);
+    });
+    if (diff.reordered) lines.push("Ordre de la barre latérale différent");
+    if (lines.length === 0) lines.push("Identique à la liste actuelle.");
+
+    const ul = document.getElementById("history-diff");
+    ul.innerHTML = "";
+    lines.forEach((line) => {
+      const li = document.createElement("li");
+      li.textContent = line;
+      ul.appendChild(li);
+    });
+    document.getElementById("history-detail").classList.remove("hidden");
+  } catch (err) {
+    showToast("Impossible de comparer cette version : " + formatInvokeError(err));
+    console.error("Diff snapshot error:", err);
+  }
+}
+
+async function restoreSelectedSnapshot() {
+  const invoke = getInvoke();
+  if (!invoke || !selectedSnapshotId) return;
+  try {
+    const applyResult = await invoke("restore_services_snapshot", { id: selectedSnapshotId });
+    showServicesLoadInfo(applyResult);
+    services = await invoke("get_services");
+    renderServices();
+    hideHistory();
+    showToast("Version restaurée.", { variant: "info" });
+  } catch (err) {
+    showToast("Impossible de restaurer cette version : " + formatInvokeError(err));
+    console.error("Restore snapshot error:", err);
+  }
+}
+
+// --- Profiles ---
+let activeProfileId = "default";
+let deleteProfileId = null; // pending confirmation in the shared confirm dialog
+
+async function loadProfiles() {
+  const invoke = getInvoke();
+  if (!invoke) return;
+  const info = await invoke("list_profiles");
+  activeProfileId = info.active;
+  const select = document.getElementById("profile-select");
+  select.innerHTML = "";
+  info.profiles.forEach((profile) => {
+    const option = document.createElement("option");
+    option.value = profile.id;
+    option.textContent = profile.id === "default" ? "Par défaut" : profile.name;
+    if (profile.id === info.active) option.textContent += " (actif)";
+    select.appendChild(option);
+  });
+  select.value = info.active;
+}
+
+async function switchProfile() {
+  const invoke = getInvoke();
+  if (!invoke) return;
+  const id = document.getElementById("profile-select").value;
+  if (!id || id === activeProfileId) return;
+  const btn = document.getElementById("profile-switch-btn");
+  btn.disabled = true;
+  try {
+    const loadInfo = await invoke("switch_profile", { id });
+    services = await invoke("get_services");
+    renderServices();
+    showPreferences(await invoke("get_preferences"));
+    loadNotificationHistory();
+    await loadProfiles();
+    showServicesLoadInfo(loadInfo);
+    showToast("Profil activé.", { variant: "info" });
+  } catch (err) {
+    showToast("Impossible de changer de profil : " + formatInvokeError(err), { durationMs: 10000 });
+    console.error("Switch profile error:", err);
+  } finally {
+    btn.disabled = false;
+  }
+}
+
+async function createProfile() {
+  const invoke = getInvoke();
+  if (!invoke) return;
+  const input = document.getElementById("profile-name");
+  const name = input.value.trim();
+  if (!name) {
+    showToast("Indiquez un nom de profil.");
+    return;
+  }
+  try {
+    const profile = await invoke("create_profile", { name });
+    input.value = "";
+    await loadProfiles();
+    document.getElementById("profile-select").value = profile.id;
+    showToast(`Profil « ${profile.name} » créé. Activez-le pour l’utiliser.`, { variant: "info" });
+  } catch (err) {
+    showToast("Impossible de créer le profil : " + formatInvokeError(err));
+  }
+}
+
+function deleteProfile() {
+  const select = document.getElementById("profile-select");
+  const id = select.value;
+  if (!id) return;
+  if (id === "default" || id === activeProfileId) {
+    showToast("Le profil par défaut et le profil actif ne peuvent pas être supprimés.");
+    return;
+  }
+  deleteProfileId = id;
+  const label = select.options[select.selectedIndex].textContent;
+  document.getElementById("confirm-msg").textContent =
+    `Supprimer le profil « ${label} » et toutes ses sessions ?`;
+  document.getElementById("confirm-dialog").classList.remove("hidden");
+}
+
+async function removeProfile(id) {
+  const invoke = getInvoke();
+  if (!invoke) return;
+  try {
+    await invoke("delete_profile", { id });
+    await loadProfiles();
+    showToast("Profil supprimé.", { variant: "info" });
+  } catch (err) {
+    showToast("Impossible de supprimer le profil : " + formatInvokeError(err));
+  }
+}
+
+// --- Workspace export / import ---
+async function exportWorkspace() {
+  const invoke = getInvoke();
+  if (!invoke) return;
+  const btn = document.getElementById("export-workspace-btn");
+  btn.disabled = true;
+  try {
+    const report = await invoke("export_workspace", {
+      path: null,
+      includeWebviewData: document.getElementById("export-sessions").checked,
+    });
+    let message = `Espace de travail exporté : ${report.path}`;
+    if (report.skipped_files.length > 0) {
+      message += ` (${report.skipped_files.length} fichier(s) de session en cours d'utilisation ignoré(s))`;
+    }
+    showToast(message, { variant: "info", durationMs: 10000 });
+  } catch (err) {
+    showToast("Impossible d’exporter l’espace de travail : " + formatInvokeError(err), { durationMs: 10000 });
+    console.error("Export workspace error:", err);
+  } finally {
+    btn.disabled = false;
+  }
+}
+
+async function importWorkspace() {
+  const invoke = getInvoke();
+  if (!invoke) return;
+  const path = document.getElementById("import-path").value.trim();
+  if (!path) {
+    showToast("Indiquez le chemin de l’archive à importer.");
+    return;
+  }
+  const btn = document.getElementById("import-workspace-btn");
+  btn.disabled = true;
+  try {
+    const report = await invoke("import_workspace", {
+      path,
+      options: {
+        include_webview_data: document.getElementById("import-sessions").checked,
+        replace_preferences: document.getElementById("import-prefs").checked,
+      },
+    });
+    services = await invoke("get_services");
+    renderServices();
+    const parts = [`${report.added.length} service(s) ajouté(s)`];
+    if (report.renamed.length > 0) parts.push(`${report.renamed.length} renommé(s)`);
+    if (report.skipped.length > 0) parts.push(`${report.skipped.length} déjà présent(s)`);
+    showToast("Import terminé : " + parts.join(", ") + ".", { variant: "info", durationMs: 8000 });
+    if (report.filtered_url_count > 0) {
+      showServicesLoadInfo(report);
+    }
+    if (report.preferences_imported) {
+      loadedPrefs = await invoke("get_preferences");
+    }
+  } catch (err) {
+    showToast("Impossible d’importer l’espace de travail : " + formatInvokeError(err), { durationMs: 10000 });
+    console.error("Import workspace error:", err);
+  } finally {
+    btn.disabled = false;
+  }
+}
+
 // --- Preferences ---
 async function initUpdates() {
   const versionEl = document.getElementById("current-version");
@@ -788,6 +1280,99 @@ async function installUpdate() {
   }
 }
 
+// --- Recent activity (notification_history.json of the active profile) ---
+async function loadNotificationHistory() {
+  const invoke = getInvoke();
+  if (!invoke) return;
+  let records;
+  try {
+    records = await invoke("get_notification_history");
+  } catch (err) {
+    console.error("Notification history error:", err);
+    return;
+  }
+  const list = document.getElementById("activity-list");
+  list.innerHTML = "";
+  document.getElementById("activity-empty").classList.toggle("hidden", records.length > 0);
+  records.forEach((record) => {
+    const service = services.find((s) => s.id === record.service_id);
+    const item = document.createElement("button");
+    item.type = "button";
+    item.className = "catalog-item";
+    item.innerHTML = `
+      <span class="icon"></span>
+      <div class="info">
+        <div class="name">${escapeHtml(record.body)}</div>
+        <div class="url">${escapeHtml(new Date(record.time).toLocaleString("fr-FR"))}</div>
+      </div>
+    `;
+    if (service) {
+      item.querySelector(".icon").appendChild(serviceIconEl(service.icon));
+      item.addEventListener("click", () => invoke("focus_service", { id: service.id }));
+    } else {
+      // Service deleted since: the entry stays readable but leads nowhere.
+      item.disabled = true;
+    }
+    list.appendChild(item);
+  });
+}
+
+// Called by the backend after each recorded notification.
+window.__reloadNotificationHistory = loadNotificationHistory;
+
+async function clearNotificationHistory() {
+  const invoke = getInvoke();
+  if (!invoke) return;
+  try {
+    await invoke("clear_notification_history");
+    await loadNotificationHistory();
+  } catch (err) {
+    showToast("Impossible d’effacer l’activité : " + formatInvokeError(err));
+  }
+}
+
+// --- Snooze (temporary Do Not Disturb, kept in memory by the backend) ---
+function showSnoozeState(untilMs) {
+  const snoozed = untilMs != null && untilMs > Date.now();
+  const status = document.getElementById("snooze-status");
+  status.textContent = snoozed
+    ? "Suspendues jusqu’à " + new Date(untilMs).toLocaleTimeString([], { hour: "2-digit", minute: "2-digit" })
+    : "Notifications actives";
+  document.getElementById("snooze-btn").classList.toggle("hidden", snoozed);
+  document.getElementById("unsnooze-btn").classList.toggle("hidden", !snoozed);
+}
+
+async function refreshSnoozeState() {
+  const invoke = getInvoke();
+  if (!invoke) return;
+  try {
+    showSnoozeState(await invoke("get_snooze_until"));
+  } catch (err) {
+    console.error("Snooze state error:", err);
+  }
+}
+
+async function snoozeNotifications(minutes) {
+  const invoke = getInvoke();
+  if (!invoke) return;
+  try {
+    showSnoozeState(await invoke("snooze_notifications", { minutes }));
+  } catch (err) {
+    showToast("Impossible de suspendre les notifications : " + formatInvokeError(err));
+  }
+}
+
+async function unsnoozeNotifications() {
+  const invoke = getInvoke();
+  if (!invoke) return;
+  try {
+    await invoke("unsnooze_notifications");
+    showSnoozeState(null);
+  } catch (err) {
+    showToast("Impossible de reprendre les notifications : " + formatInvokeError(err));
+  }
+}
+
 async function savePreferences() {
   const invoke = getInvoke();
   if (!invoke) return;
@@ -800,7 +1385,17 @@ async function savePreferences() {
     theme: selectedTheme,
     accent_color: selectedAccent,
     notifications_enabled: document.getElementById("pref-notifications").checked,
+    notification_interval_seconds: parseInt(document.getElementById("pref-notification-interval").value, 10),
     hibernation_minutes: parseInt(document.getElementById("pref-hibernation").value, 10),
+    memory_limit_mb: parseInt(document.getElementById("pref-memory-limit").value, 10),
+    restore_last_page: document.getElementById("pref-restore-last-page").checked,
+    notification_focus_switches: document.getElementById("pref-notification-focus").checked,
+    quiet_hours: {
+      enabled: document.getElementById("pref-quiet-enabled").checked,
+      start: document.getElementById("pref-quiet-start").value || "19:00",
+      end: document.getElementById("pref-quiet-end").value || "08:00",
+      weekends: document.getElementById("pref-quiet-weekends").checked,
+    },
   };
 
   try {
@@ -826,6 +1421,19 @@ async function savePreferences() {
   }
 }
 
+// Called from Rust when services.json was edited outside the app, so a later
+// save from this page doesn't overwrite the external change.
+window.__reloadServices = async function() {
+  const invoke = getInvoke();
+  if (!invoke) return;
+  try {
+    services = await invoke("get_services");
+    renderServices();
+  } catch (err) {
+    console.error("Reload services error:", err);
+  }
+};
+
 function escapeHtml(text) {
   const div = document.createElement("div");
   div.textContent = text;
@@ -45,4 +45,21 @@ export function showServicesLoadInfo(info) {
       { variant: "warning", durationMs: 8000 },
     );
   }
+
+  if (info.skipped_entries && info.skipped_entries.length > 0) {
+    const details = info.skipped_entries
+      .map((entry) => `#${entry.index}${entry.id ? ` (${entry.id})` : ""}`)
+      .join(", ");
+    showToast(
+      `Skipped unreadable services.json entries: ${details}. Original kept in services.json.partial.bak.`,
+      { variant: "warning", durationMs: 10000 },
+    );
+  }
+
+  if (info.applied_migrations && info.applied_migrations.length > 0) {
+    showToast(
+      "services.json upgraded to the current format (previous version backed up).",
+      { variant: "info", durationMs: 8000 },
+    );
+  }
 }